@@ -0,0 +1,63 @@
+use std::fmt::Debug;
+use std::io::Write;
+
+use crate::callbacks::{Callback, EpochLogs};
+
+/// Writes one `Epoch N - Loss: ...` line every `every` epochs.
+/// `Sequential::train` reports nothing unless a callback such as this one is registered.
+pub struct Logger {
+    every: usize,
+    writer: Box<dyn Write>,
+}
+
+impl Logger {
+    /// Logs every epoch to stdout.
+    pub fn new() -> Self {
+        Logger {
+            every: 1,
+            writer: Box::new(std::io::stdout()),
+        }
+    }
+
+    pub fn to_writer(writer: impl Write + 'static) -> Self {
+        Logger {
+            every: 1,
+            writer: Box::new(writer),
+        }
+    }
+
+    pub fn every(mut self, epochs: usize) -> Self {
+        if epochs == 0 {
+            panic!("Error: Logging interval must be at least 1");
+        }
+        self.every = epochs;
+        self
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Callback<T> for Logger
+where
+    T: Debug,
+{
+    fn on_epoch_end(&mut self, logs: &EpochLogs<T>) {
+        if !logs.epoch.is_multiple_of(self.every) {
+            return;
+        }
+
+        let mut line = format!("Epoch {} - Loss: {:?}", logs.epoch, logs.loss);
+        if let Some(val_loss) = &logs.val_loss {
+            line.push_str(&format!(" - Val loss: {:?}", val_loss));
+        }
+        for (name, value) in &logs.metrics {
+            line.push_str(&format!(" - {}: {:?}", name, value));
+        }
+        // Logging must never abort training
+        let _ = writeln!(self.writer, "{}", line);
+    }
+}
@@ -0,0 +1,32 @@
+pub mod logger;
+pub mod progress;
+pub mod silent;
+
+use crate::model::history::TrainingHistory;
+
+/// Values reported at the end of every training step (one input tensor).
+pub struct BatchLogs<T> {
+    pub epoch: usize, // 1-based
+    pub batch: usize, // 1-based
+    pub loss: T,
+}
+
+/// Values reported at the end of every epoch.
+pub struct EpochLogs<T> {
+    pub epoch: usize, // 1-based
+    /// Mean training loss of the epoch
+    pub loss: T,
+    /// Loss on the validation data, `None` when the model has none
+    pub val_loss: Option<T>,
+    /// Named metric values, e.g. `("grad_norm", 0.3)`
+    pub metrics: Vec<(String, T)>,
+}
+
+/// Hooks called by `Sequential::train`. Every hook defaults to doing nothing.
+pub trait Callback<T> {
+    fn on_train_begin(&mut self, _epochs: usize, _batches_per_epoch: usize) {}
+    fn on_batch_end(&mut self, _logs: &BatchLogs<T>) {}
+    fn on_epoch_end(&mut self, _logs: &EpochLogs<T>) {}
+    /// Receives the history that `train` is about to return
+    fn on_train_end(&mut self, _history: &TrainingHistory<T>) {}
+}
@@ -0,0 +1,81 @@
+use std::fmt::Debug;
+use std::io::Write;
+
+use crate::callbacks::{BatchLogs, Callback, EpochLogs};
+
+/// Single-line progress bar on stderr, redrawn after every step.
+pub struct ProgressBar {
+    width: usize,
+    epochs: usize,
+    batches: usize,
+}
+
+impl ProgressBar {
+    pub fn new() -> Self {
+        ProgressBar {
+            width: 30,
+            epochs: 0,
+            batches: 0,
+        }
+    }
+
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    fn bar(&self, done: usize) -> String {
+        let filled = (done * self.width).checked_div(self.batches).unwrap_or(self.width);
+        format!("[{}{}]", "=".repeat(filled), " ".repeat(self.width - filled))
+    }
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Callback<T> for ProgressBar
+where
+    T: Debug,
+{
+    fn on_train_begin(&mut self, epochs: usize, batches_per_epoch: usize) {
+        self.epochs = epochs;
+        self.batches = batches_per_epoch;
+    }
+
+    fn on_batch_end(&mut self, logs: &BatchLogs<T>) {
+        let mut stderr = std::io::stderr();
+        let _ = write!(
+            stderr,
+            "\rEpoch {}/{} {} {}/{} - loss: {:?}",
+            logs.epoch,
+            self.epochs,
+            self.bar(logs.batch),
+            logs.batch,
+            self.batches,
+            logs.loss
+        );
+        let _ = stderr.flush();
+    }
+
+    fn on_epoch_end(&mut self, logs: &EpochLogs<T>) {
+        let mut line = format!(
+            "\rEpoch {}/{} {} {}/{} - loss: {:?}",
+            logs.epoch,
+            self.epochs,
+            self.bar(self.batches),
+            self.batches,
+            self.batches,
+            logs.loss
+        );
+        if let Some(val_loss) = &logs.val_loss {
+            line.push_str(&format!(" - val_loss: {:?}", val_loss));
+        }
+        for (name, value) in &logs.metrics {
+            line.push_str(&format!(" - {}: {:?}", name, value));
+        }
+        let _ = writeln!(std::io::stderr(), "{}", line);
+    }
+}
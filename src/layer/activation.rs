@@ -7,8 +7,12 @@ where
     if x > T::default() { x } else { T::default() }
 }
 
+use std::ops::{Add, Mul, Sub};
+
 use num_traits::Float;
 
+use crate::tensor::Tensor;
+
 pub fn sigmoid<T>(x: T) -> T
 where
     T: Float,
@@ -24,3 +28,14 @@ where
     let one: T = T::one();
     (one.exp() - (-x).exp()) / (one.exp() + (-x).exp())
 }
+
+/// Applies the optional output activation of a layer to every element of `output`
+pub(crate) fn activate<T>(output: Tensor<T>, activation: Option<ActivationFn<T>>) -> Tensor<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    match activation {
+        Some(activation_fn) => output.map(activation_fn),
+        None => output,
+    }
+}
@@ -0,0 +1,495 @@
+use std::ops::{Add, Mul, Sub};
+use std::rc::Rc;
+
+use num_traits::Float;
+use rand::Rng;
+
+use crate::layer::dropout::keep_mask;
+use crate::layer::initializer::Initializer;
+use crate::tensor::Tensor;
+use crate::types::{Accuracy, Randomizable};
+
+use super::activation::activate;
+use super::trainable::{NamedTensors, NamedTensorsMut, PassState, TrainableLayer};
+
+/// Affine map `x W + b` with its accumulated gradients
+struct Projection<T> {
+    weights: Tensor<T>,
+    bias: Tensor<T>,
+    grad_weights: Tensor<T>,
+    grad_bias: Tensor<T>,
+}
+
+impl<T> Projection<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    fn new<R: Rng + ?Sized>(
+        size: usize,
+        accuracy: Accuracy,
+        weight_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+        rng: &mut R,
+    ) -> Self {
+        Projection {
+            weights: weight_init.initialize_with_rng(vec![size, size], size, size, accuracy, rng),
+            bias: bias_init.initialize_with_rng(vec![size], size, size, accuracy, rng),
+            grad_weights: Tensor::zeros(accuracy, vec![size, size]),
+            grad_bias: Tensor::zeros(accuracy, vec![size]),
+        }
+    }
+}
+
+impl<T> Projection<T>
+where
+    T: Float + Default,
+{
+    fn apply(&self, x: &Tensor<T>) -> Tensor<T> {
+        x.matmul(&self.weights).add(&self.bias)
+    }
+
+    /// Accumulates the parameter gradients and returns the gradient of `x`
+    fn backward(&mut self, x: &Tensor<T>, grad: &Tensor<T>) -> Tensor<T> {
+        self.grad_weights = self.grad_weights.add(&x.transpose().matmul(grad));
+        self.grad_bias = self.grad_bias.add(&grad.sum(0));
+        grad.matmul(&self.weights.transpose())
+    }
+}
+
+/// Intermediate values of a forward pass, reused by `backward`
+struct AttentionPass<T> {
+    x: Tensor<T>,
+    q: Tensor<T>,
+    k: Tensor<T>,
+    v: Tensor<T>,
+    /// Softmax output of every (batch, head), `seq * seq` values
+    weights: Vec<Vec<T>>,
+    /// The same weights after dropout, `[seq, seq]`
+    probs: Vec<Tensor<T>>,
+    context: Tensor<T>,
+    output: Tensor<T>,
+}
+
+/// Masks a training forward was run with, so `backward` recomputes the same pass
+struct MaskState<T> {
+    /// Dropout mask of every (batch, head), `None` without dropout
+    dropout: Option<Vec<Vec<T>>>,
+    padding: Option<Tensor<T>>,
+}
+
+/// Multi-head scaled dot-product self-attention over `[batch, seq, d_model]` input.
+///
+/// The input is projected to queries, keys and values, split into `num_heads`
+/// heads of size `d_model / num_heads`, each head computes
+/// `softmax(q k^T / sqrt(head_size)) v`, and the concatenated heads go through
+/// an output projection. Keys can be hidden with a causal mask (no attending to
+/// later positions) and a padding mask; fully masked rows attend to nothing.
+/// In training mode the attention weights go through inverted dropout.
+pub struct MultiHeadAttention<T> {
+    query: Projection<T>,
+    key: Projection<T>,
+    value: Projection<T>,
+    output: Projection<T>,
+    num_heads: usize,
+    causal: bool,
+    dropout: f32,
+    padding_mask: Option<Tensor<T>>,
+    training: bool,
+    /// Masks of the last `forward_train`
+    pass: Option<Rc<MaskState<T>>>,
+}
+
+impl<T> MultiHeadAttention<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    /// Glorot-uniform projection weights and zero biases
+    pub fn new(d_model: usize, num_heads: usize, accuracy: Accuracy) -> Self {
+        Self::with_initializers(d_model, num_heads, accuracy, &Initializer::XavierUniform, &Initializer::Zeros)
+    }
+
+    pub fn with_initializers(
+        d_model: usize,
+        num_heads: usize,
+        accuracy: Accuracy,
+        weight_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+    ) -> Self {
+        crate::rng::with_rng(|rng| {
+            Self::with_initializers_and_rng(d_model, num_heads, accuracy, weight_init, bias_init, rng)
+        })
+    }
+
+    pub fn with_initializers_and_rng<R: Rng + ?Sized>(
+        d_model: usize,
+        num_heads: usize,
+        accuracy: Accuracy,
+        weight_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+        rng: &mut R,
+    ) -> Self {
+        if num_heads == 0 || d_model == 0 || !d_model.is_multiple_of(num_heads) {
+            panic!("Error: d_model must be a positive multiple of num_heads");
+        }
+
+        MultiHeadAttention {
+            query: Projection::new(d_model, accuracy, weight_init, bias_init, rng),
+            key: Projection::new(d_model, accuracy, weight_init, bias_init, rng),
+            value: Projection::new(d_model, accuracy, weight_init, bias_init, rng),
+            output: Projection::new(d_model, accuracy, weight_init, bias_init, rng),
+            num_heads,
+            causal: false,
+            dropout: 0.0,
+            padding_mask: None,
+            training: false,
+            pass: None,
+        }
+    }
+
+    /// Every position only attends to itself and earlier positions
+    pub fn with_causal(mut self, causal: bool) -> Self {
+        self.causal = causal;
+        self
+    }
+
+    /// Dropout rate applied to the attention weights in training mode
+    pub fn with_dropout(mut self, rate: f32) -> Self {
+        if !(0.0..1.0).contains(&rate) {
+            panic!("Error: Dropout rate must be in [0, 1)");
+        }
+        self.dropout = rate;
+        self
+    }
+}
+
+impl<T> MultiHeadAttention<T>
+where
+    T: 'static + Float + Default,
+{
+    /// `[batch, seq]` mask of the keys that can be attended to (non-zero) or are padding (zero).
+    /// `None` lets every key through.
+    ///
+    /// `Sequential::train` uses one mask for every batch; to change it per batch, set it
+    /// through `Sequential::layer_mut` before each `forward_train` of a custom loop. A
+    /// pass keeps the mask it was run with, so changing it does not affect `backward`.
+    pub fn set_padding_mask(&mut self, mask: Option<Tensor<T>>) {
+        if let Some(mask) = &mask
+            && mask.get_shape().len() != 2
+        {
+            panic!("Error: Padding mask must be a [batch, seq] tensor");
+        }
+        self.padding_mask = mask;
+    }
+
+    pub fn get_num_heads(&self) -> usize {
+        self.num_heads
+    }
+
+    fn d_model(&self) -> usize {
+        self.query.weights.get_shape()[0]
+    }
+
+    fn dims(&self, input: &Tensor<T>, padding: Option<&Tensor<T>>) -> Result<(usize, usize), String> {
+        let shape = input.get_shape();
+        if shape.len() != 3 || shape[2] != self.d_model() {
+            return Err("Input tensor must be [batch, seq, d_model]".into());
+        }
+        if shape[0] * shape[1] == 0 {
+            return Err("Input sequence is empty".into());
+        }
+        if let Some(mask) = padding
+            && mask.get_shape() != &vec![shape[0], shape[1]]
+        {
+            return Err("Padding mask shape does not match the input [batch, seq]".into());
+        }
+        Ok((shape[0], shape[1]))
+    }
+
+    fn dropout_active(&self) -> bool {
+        self.training && self.dropout > 0.0
+    }
+
+    /// One inverted-dropout mask over the `[seq, seq]` weights of every (batch, head)
+    fn sample_masks(&self, batch: usize, seq: usize) -> Vec<Vec<T>> {
+        let scale = T::one() / T::from(1.0 - self.dropout).unwrap();
+        (0..batch * self.num_heads)
+            .map(|_| {
+                keep_mask(seq * seq, self.dropout)
+                    .into_iter()
+                    .map(|keep| if keep { scale } else { T::zero() })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn allowed(&self, padding: Option<&Tensor<T>>, b: usize, seq: usize, i: usize, j: usize) -> bool {
+        let causal_ok = !self.causal || j <= i;
+        let padding_ok = match padding {
+            Some(mask) => mask.get_data()[b * seq + j] != T::zero(),
+            None => true,
+        };
+        causal_ok && padding_ok
+    }
+
+    /// `[seq, head_size]` block of head `h` of sample `b` in a `[batch * seq, d_model]` matrix
+    fn head_block(&self, m: &Tensor<T>, b: usize, h: usize, seq: usize) -> Tensor<T> {
+        let d = self.d_model();
+        let size = d / self.num_heads;
+        let mut data = Vec::with_capacity(seq * size);
+        for i in 0..seq {
+            let start = (b * seq + i) * d + h * size;
+            data.extend_from_slice(&m.get_data()[start..start + size]);
+        }
+        Tensor::new(*m.get_accuracy(), data, vec![seq, size])
+    }
+
+    fn write_head_block(&self, dst: &mut [T], block: &Tensor<T>, b: usize, h: usize, seq: usize) {
+        let d = self.d_model();
+        let size = d / self.num_heads;
+        for i in 0..seq {
+            let start = (b * seq + i) * d + h * size;
+            dst[start..start + size].copy_from_slice(&block.get_data()[i * size..(i + 1) * size]);
+        }
+    }
+
+    /// Masked row softmax of `scores` (`[seq, seq]`); rows with every key masked become zero
+    fn softmax(&self, scores: &Tensor<T>, padding: Option<&Tensor<T>>, b: usize, seq: usize) -> Vec<T> {
+        let mut probs = vec![T::zero(); seq * seq];
+        for i in 0..seq {
+            let row = &scores.get_data()[i * seq..(i + 1) * seq];
+            let keys: Vec<usize> = (0..seq).filter(|&j| self.allowed(padding, b, seq, i, j)).collect();
+            if keys.is_empty() {
+                continue;
+            }
+            let max = keys.iter().fold(T::neg_infinity(), |m, &j| m.max(row[j]));
+            let total = keys.iter().fold(T::zero(), |acc, &j| acc + (row[j] - max).exp());
+            for &j in &keys {
+                probs[i * seq + j] = (row[j] - max).exp() / total;
+            }
+        }
+        probs
+    }
+
+    fn run(
+        &self,
+        input: &Tensor<T>,
+        masks: Option<&[Vec<T>]>,
+        padding: Option<&Tensor<T>>,
+    ) -> Result<AttentionPass<T>, String> {
+        let (batch, seq) = self.dims(input, padding)?;
+        let d = self.d_model();
+        let accuracy = *input.get_accuracy();
+        let scale = T::one() / T::from(d / self.num_heads).unwrap().sqrt();
+
+        let x = input.reshape(vec![batch * seq, d]);
+        let (q, k, v) = (self.query.apply(&x), self.key.apply(&x), self.value.apply(&x));
+
+        let mut weights = Vec::with_capacity(batch * self.num_heads);
+        let mut probs = Vec::with_capacity(batch * self.num_heads);
+        let mut context = vec![T::zero(); batch * seq * d];
+        for b in 0..batch {
+            for h in 0..self.num_heads {
+                let q_h = self.head_block(&q, b, h, seq);
+                let k_h = self.head_block(&k, b, h, seq);
+                let scores = q_h.matmul(&k_h.transpose()).scale(scale);
+                let w = self.softmax(&scores, padding, b, seq);
+                let mut p = w.clone();
+                if let Some(masks) = masks {
+                    for (value, &m) in p.iter_mut().zip(&masks[b * self.num_heads + h]) {
+                        *value = *value * m;
+                    }
+                }
+                let p = Tensor::new(accuracy, p, vec![seq, seq]);
+                let ctx = p.matmul(&self.head_block(&v, b, h, seq));
+                self.write_head_block(&mut context, &ctx, b, h, seq);
+                weights.push(w);
+                probs.push(p);
+            }
+        }
+
+        let context = Tensor::new(accuracy, context, vec![batch * seq, d]);
+        let output = self.output.apply(&context).reshape(vec![batch, seq, d]);
+        Ok(AttentionPass {
+            x,
+            q,
+            k,
+            v,
+            weights,
+            probs,
+            context,
+            output,
+        })
+    }
+
+    /// Attention weights `[batch, heads, seq, seq]` of `input` in inference mode
+    pub fn attention_weights(&self, input: &Tensor<T>) -> Result<Tensor<T>, String> {
+        let (batch, seq) = self.dims(input, self.padding_mask.as_ref())?;
+        let pass = self.run(input, None, self.padding_mask.as_ref())?;
+        let data = pass.weights.concat();
+        Ok(Tensor::new(*input.get_accuracy(), data, vec![batch, self.num_heads, seq, seq]))
+    }
+}
+
+impl<T> TrainableLayer<T> for MultiHeadAttention<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let padding = self.padding_mask.as_ref();
+        let (batch, seq) = self.dims(input, padding)?;
+        let masks = self.dropout_active().then(|| self.sample_masks(batch, seq));
+        let output = self.run(input, masks.as_deref(), padding)?.output;
+
+        Ok(activate(output, activation))
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let padding = self.padding_mask.as_ref();
+        let (batch, seq) = self.dims(input, padding)?;
+        let masks = self.dropout_active().then(|| self.sample_masks(batch, seq));
+        let output = self.run(input, masks.as_deref(), padding)?.output;
+        self.pass = Some(Rc::new(MaskState {
+            dropout: masks,
+            padding: self.padding_mask.clone(),
+        }));
+
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        if grad_output.get_shape() != input.get_shape() {
+            panic!("Error: Output gradient shape does not match the attention output");
+        }
+        let state = self.pass.clone().unwrap_or_else(|| {
+            if self.dropout_active() {
+                panic!("Error: Attention backward with dropout needs a forward_train first");
+            }
+            Rc::new(MaskState {
+                dropout: None,
+                padding: self.padding_mask.clone(),
+            })
+        });
+        let (masks, padding) = (state.dropout.as_deref(), state.padding.as_ref());
+        let (batch, seq) = self.dims(input, padding).unwrap();
+        if masks.is_some_and(|masks| masks.len() != batch * self.num_heads || masks[0].len() != seq * seq) {
+            panic!("Error: Input shape does not match the pass being backpropagated");
+        }
+        let pass = self.run(input, masks, padding).unwrap();
+
+        let d = self.d_model();
+        let accuracy = *input.get_accuracy();
+        let scale = T::one() / T::from(d / self.num_heads).unwrap().sqrt();
+        let grad_out = grad_output.reshape(vec![batch * seq, d]);
+        let grad_context = self.output.backward(&pass.context, &grad_out);
+
+        let mut grad_q = vec![T::zero(); batch * seq * d];
+        let mut grad_k = vec![T::zero(); batch * seq * d];
+        let mut grad_v = vec![T::zero(); batch * seq * d];
+        for b in 0..batch {
+            for h in 0..self.num_heads {
+                let idx = b * self.num_heads + h;
+                let p = &pass.probs[idx];
+                let (q_h, k_h, v_h) = (
+                    self.head_block(&pass.q, b, h, seq),
+                    self.head_block(&pass.k, b, h, seq),
+                    self.head_block(&pass.v, b, h, seq),
+                );
+                let grad_ctx = self.head_block(&grad_context, b, h, seq);
+
+                self.write_head_block(&mut grad_v, &p.transpose().matmul(&grad_ctx), b, h, seq);
+                let mut grad_p = grad_ctx.matmul(&v_h.transpose()).get_data().clone();
+
+                // Back through dropout to the softmax output
+                if let Some(masks) = masks {
+                    for (g, &m) in grad_p.iter_mut().zip(&masks[idx]) {
+                        *g = *g * m;
+                    }
+                }
+                let soft = &pass.weights[idx];
+
+                // Softmax: dS = P * (dP - sum(dP * P))
+                let mut grad_scores = vec![T::zero(); seq * seq];
+                for i in 0..seq {
+                    let row = i * seq..(i + 1) * seq;
+                    let dot = soft[row.clone()]
+                        .iter()
+                        .zip(&grad_p[row.clone()])
+                        .fold(T::zero(), |acc, (&s, &g)| acc + s * g);
+                    for j in row {
+                        grad_scores[j] = soft[j] * (grad_p[j] - dot) * scale;
+                    }
+                }
+                let grad_scores = Tensor::new(accuracy, grad_scores, vec![seq, seq]);
+
+                self.write_head_block(&mut grad_q, &grad_scores.matmul(&k_h), b, h, seq);
+                self.write_head_block(&mut grad_k, &grad_scores.transpose().matmul(&q_h), b, h, seq);
+            }
+        }
+
+        let shape = vec![batch * seq, d];
+        let grad_x = self
+            .query
+            .backward(&pass.x, &Tensor::new(accuracy, grad_q, shape.clone()))
+            .add(&self.key.backward(&pass.x, &Tensor::new(accuracy, grad_k, shape.clone())))
+            .add(&self.value.backward(&pass.x, &Tensor::new(accuracy, grad_v, shape)));
+        grad_x.reshape(input.get_shape().clone())
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        let mut params = Vec::with_capacity(8);
+        for (name, p) in [("query", &self.query), ("key", &self.key), ("value", &self.value), ("output", &self.output)] {
+            params.push((format!("{}_weights", name), &p.weights));
+            params.push((format!("{}_bias", name), &p.bias));
+        }
+        params
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        let mut params = Vec::with_capacity(8);
+        for (name, p) in [
+            ("query", &mut self.query),
+            ("key", &mut self.key),
+            ("value", &mut self.value),
+            ("output", &mut self.output),
+        ] {
+            params.push((format!("{}_weights", name), &mut p.weights));
+            params.push((format!("{}_bias", name), &mut p.bias));
+        }
+        params
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        let mut grads = Vec::with_capacity(8);
+        for (name, p) in [("query", &self.query), ("key", &self.key), ("value", &self.value), ("output", &self.output)] {
+            grads.push((format!("{}_weights", name), &p.grad_weights));
+            grads.push((format!("{}_bias", name), &p.grad_bias));
+        }
+        grads
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        let mut grads = Vec::with_capacity(8);
+        for (name, p) in [
+            ("query", &mut self.query),
+            ("key", &mut self.key),
+            ("value", &mut self.value),
+            ("output", &mut self.output),
+        ] {
+            grads.push((format!("{}_weights", name), &mut p.grad_weights));
+            grads.push((format!("{}_bias", name), &mut p.grad_bias));
+        }
+        grads
+    }
+
+    fn pass_state(&self) -> Option<PassState> {
+        self.pass.clone().map(|pass| pass as PassState)
+    }
+
+    fn set_pass_state(&mut self, state: Option<PassState>) {
+        self.pass = state.and_then(|state| state.downcast::<MaskState<T>>().ok());
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
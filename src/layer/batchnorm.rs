@@ -0,0 +1,311 @@
+use std::marker::PhantomData;
+
+use num_traits::Float;
+
+use crate::tensor::Tensor;
+use crate::types::Accuracy;
+
+use super::activation::activate;
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
+
+/// (x_hat, inv_std, mean, var) of a normalised input
+type Normalized<T> = (Vec<T>, Vec<T>, Vec<T>, Vec<T>);
+
+/// Input ranks accepted by a `BatchNorm` flavour.
+pub trait BatchNormRanks {
+    const RANKS: &'static [usize];
+}
+
+/// `[batch, features]` or `[batch, channels, length]` input
+pub struct Ranks1d;
+
+/// `[batch, channels, height, width]` input
+pub struct Ranks2d;
+
+impl BatchNormRanks for Ranks1d {
+    const RANKS: &'static [usize] = &[2, 3];
+}
+
+impl BatchNormRanks for Ranks2d {
+    const RANKS: &'static [usize] = &[4];
+}
+
+/// Normalisation of axis 1 (channels) over the batch and every trailing axis.
+///
+/// In training mode every channel is normalised with the statistics of the
+/// current batch and the running mean/variance are updated by `forward_train`;
+/// in inference mode the running statistics are used instead.
+pub struct BatchNorm<T, R> {
+    gamma: Tensor<T>,
+    beta: Tensor<T>,
+    grad_gamma: Tensor<T>,
+    grad_beta: Tensor<T>,
+    running_mean: Tensor<T>,
+    running_var: Tensor<T>,
+    momentum: T,
+    epsilon: T,
+    training: bool,
+    ranks: PhantomData<R>,
+}
+
+/// Batch normalisation of `[batch, features]` or `[batch, channels, length]` input.
+pub type BatchNorm1d<T> = BatchNorm<T, Ranks1d>;
+
+/// Batch normalisation of `[batch, channels, height, width]` input, per channel.
+pub type BatchNorm2d<T> = BatchNorm<T, Ranks2d>;
+
+impl<T, R> BatchNorm<T, R>
+where
+    T: 'static + Float + Default,
+    R: BatchNormRanks,
+{
+    pub fn new(num_features: usize, accuracy: Accuracy) -> Self {
+        if num_features == 0 {
+            panic!("Error: BatchNorm needs at least one feature");
+        }
+        let ones = Tensor::new(accuracy, vec![T::one(); num_features], vec![num_features]);
+        let zeros = Tensor::zeros(accuracy, vec![num_features]);
+
+        BatchNorm {
+            gamma: ones.clone(),
+            beta: zeros.clone(),
+            grad_gamma: zeros.clone(),
+            grad_beta: zeros.clone(),
+            running_mean: zeros,
+            running_var: ones,
+            momentum: T::from(0.1).unwrap(),
+            epsilon: T::from(1e-5).unwrap(),
+            training: false,
+            ranks: PhantomData,
+        }
+    }
+
+    /// Weight of the current batch in the running statistics (0.1 by default)
+    pub fn with_momentum(mut self, momentum: T) -> Self {
+        self.momentum = momentum;
+        self
+    }
+
+    /// Added to the variance before the square root (1e-5 by default)
+    pub fn with_epsilon(mut self, epsilon: T) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn get_gamma(&self) -> &Tensor<T> {
+        &self.gamma
+    }
+
+    pub fn get_beta(&self) -> &Tensor<T> {
+        &self.beta
+    }
+
+    pub fn get_running_mean(&self) -> &Tensor<T> {
+        &self.running_mean
+    }
+
+    pub fn get_running_var(&self) -> &Tensor<T> {
+        &self.running_var
+    }
+
+    pub fn is_training(&self) -> bool {
+        self.training
+    }
+
+    /// (batch, channels, trailing size) of a valid input
+    fn dims(&self, input: &Tensor<T>) -> Result<(usize, usize, usize), String> {
+        let shape = input.get_shape();
+        if !R::RANKS.contains(&shape.len()) {
+            return Err(format!("Input tensor must have {:?} dimensions, got {}", R::RANKS, shape.len()));
+        }
+        if shape[1] != self.gamma.get_size() {
+            return Err("Input channels do not match the number of features".into());
+        }
+        let spatial: usize = shape[2..].iter().product();
+        if shape[0] * spatial == 0 {
+            return Err("Input tensor is empty".into());
+        }
+        Ok((shape[0], shape[1], spatial))
+    }
+
+    /// Mean and biased variance of every channel
+    fn batch_stats(&self, input: &Tensor<T>) -> Result<(Vec<T>, Vec<T>), String> {
+        let (batch, channels, spatial) = self.dims(input)?;
+        let data = input.get_data();
+        let count = T::from(batch * spatial).unwrap();
+        let mut mean = vec![T::zero(); channels];
+        let mut var = vec![T::zero(); channels];
+
+        for (i, &x) in data.iter().enumerate() {
+            let c = (i / spatial) % channels;
+            mean[c] = mean[c] + x;
+        }
+        for m in mean.iter_mut() {
+            *m = *m / count;
+        }
+        for (i, &x) in data.iter().enumerate() {
+            let c = (i / spatial) % channels;
+            let d = x - mean[c];
+            var[c] = var[c] + d * d;
+        }
+        for v in var.iter_mut() {
+            *v = *v / count;
+        }
+        Ok((mean, var))
+    }
+
+    /// Normalised input and per-channel `1 / sqrt(var + eps)`, using batch or running statistics
+    fn normalize(&self, input: &Tensor<T>, batch_stats: bool) -> Result<Normalized<T>, String> {
+        let (_, channels, spatial) = self.dims(input)?;
+        let (mean, var) = if batch_stats {
+            self.batch_stats(input)?
+        } else {
+            (self.running_mean.get_data().clone(), self.running_var.get_data().clone())
+        };
+        let inv_std: Vec<T> = var.iter().map(|&v| T::one() / (v + self.epsilon).sqrt()).collect();
+        let x_hat = input
+            .get_data()
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let c = (i / spatial) % channels;
+                (x - mean[c]) * inv_std[c]
+            })
+            .collect();
+        Ok((x_hat, inv_std, mean, var))
+    }
+
+    fn output(&self, input: &Tensor<T>, x_hat: &[T], activation: Option<fn(T) -> T>) -> Tensor<T> {
+        let channels = self.gamma.get_size();
+        let spatial: usize = input.get_shape()[2..].iter().product();
+        let (gamma, beta) = (self.gamma.get_data(), self.beta.get_data());
+        let data = x_hat
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| {
+                let c = (i / spatial) % channels;
+                gamma[c] * x + beta[c]
+            })
+            .collect();
+        let output = Tensor::new(*input.get_accuracy(), data, input.get_shape().clone());
+
+        activate(output, activation)
+    }
+}
+
+impl<T, R> TrainableLayer<T> for BatchNorm<T, R>
+where
+    T: 'static + Float + Default,
+    R: 'static + BatchNormRanks,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let (x_hat, _, _, _) = self.normalize(input, self.training)?;
+        Ok(self.output(input, &x_hat, activation))
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let (x_hat, _, mean, var) = self.normalize(input, self.training)?;
+
+        if self.training {
+            // Running variance uses the unbiased estimate
+            let (batch, _, spatial) = self.dims(input)?;
+            let count = batch * spatial;
+            let correction = if count > 1 {
+                T::from(count).unwrap() / T::from(count - 1).unwrap()
+            } else {
+                T::one()
+            };
+            let m = self.momentum;
+            let update = |running: &Tensor<T>, batch: &[T], scale: T| {
+                let data = running
+                    .get_data()
+                    .iter()
+                    .zip(batch)
+                    .map(|(&r, &b)| (T::one() - m) * r + m * b * scale)
+                    .collect();
+                Tensor::new(*running.get_accuracy(), data, running.get_shape().clone())
+            };
+            self.running_mean = update(&self.running_mean, &mean, T::one());
+            self.running_var = update(&self.running_var, &var, correction);
+        }
+
+        Ok(self.output(input, &x_hat, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let (batch, channels, spatial) = self.dims(input).unwrap();
+        if grad_output.get_shape() != input.get_shape() {
+            panic!("Error: Output gradient shape does not match the BatchNorm output");
+        }
+
+        // Batch statistics come from `input` itself; running statistics only change in training mode
+        let batch_stats = self.training;
+        let (x_hat, inv_std, _, _) = self.normalize(input, batch_stats).unwrap();
+
+        let grad = grad_output.get_data();
+        let mut sum_grad = vec![T::zero(); channels];
+        let mut sum_grad_x_hat = vec![T::zero(); channels];
+        for (i, (&g, &x)) in grad.iter().zip(&x_hat).enumerate() {
+            let c = (i / spatial) % channels;
+            sum_grad[c] = sum_grad[c] + g;
+            sum_grad_x_hat[c] = sum_grad_x_hat[c] + g * x;
+        }
+
+        let gamma = self.gamma.get_data();
+        let count = T::from(batch * spatial).unwrap();
+        let grad_input = grad
+            .iter()
+            .zip(&x_hat)
+            .enumerate()
+            .map(|(i, (&g, &x))| {
+                let c = (i / spatial) % channels;
+                if batch_stats {
+                    // The batch mean and variance depend on every input of the channel
+                    gamma[c] * inv_std[c] * (g - sum_grad[c] / count - x * sum_grad_x_hat[c] / count)
+                } else {
+                    gamma[c] * inv_std[c] * g
+                }
+            })
+            .collect();
+
+        let accuracy = *input.get_accuracy();
+        self.grad_gamma = self.grad_gamma.add(&Tensor::new(accuracy, sum_grad_x_hat, vec![channels]));
+        self.grad_beta = self.grad_beta.add(&Tensor::new(accuracy, sum_grad, vec![channels]));
+
+        Tensor::new(accuracy, grad_input, input.get_shape().clone())
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![("gamma".into(), &self.gamma), ("beta".into(), &self.beta)]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("gamma".into(), &mut self.gamma), ("beta".into(), &mut self.beta)]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![("gamma".into(), &self.grad_gamma), ("beta".into(), &self.grad_beta)]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("gamma".into(), &mut self.grad_gamma), ("beta".into(), &mut self.grad_beta)]
+    }
+
+    fn buffers(&self) -> NamedTensors<'_, T> {
+        vec![
+            ("running_mean".into(), &self.running_mean),
+            ("running_var".into(), &self.running_var),
+        ]
+    }
+
+    fn buffers_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![
+            ("running_mean".into(), &mut self.running_mean),
+            ("running_var".into(), &mut self.running_var),
+        ]
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
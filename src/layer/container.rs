@@ -0,0 +1,580 @@
+use std::ops::{Add, Mul, Sub};
+use std::rc::Rc;
+
+use crate::tensor::Tensor;
+
+use super::activation::activate;
+use super::trainable::{NamedTensors, NamedTensorsMut, PassState, TrainableLayer};
+
+/// Adds `input` to the output of `inner`: `y = x + inner(x)`.
+/// `inner` must keep the shape of its input.
+pub struct Residual<T> {
+    inner: Box<dyn TrainableLayer<T>>,
+}
+
+impl<T> Residual<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    pub fn new(inner: impl TrainableLayer<T> + 'static) -> Self {
+        Residual { inner: Box::new(inner) }
+    }
+
+    fn merge(input: &Tensor<T>, output: Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        if output.get_shape() != input.get_shape() {
+            return Err(format!(
+                "Residual inner layer changed the shape from {:?} to {:?}",
+                input.get_shape(),
+                output.get_shape()
+            ));
+        }
+        Ok(activate(input.add(&output), activation))
+    }
+}
+
+impl<T> TrainableLayer<T> for Residual<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        Self::merge(input, self.inner.forward(input, None)?, activation)
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        Self::merge(input, self.inner.forward_train(input, None)?, activation)
+    }
+
+    /// The gradient reaches the input both directly and through `inner`
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        grad_output.add(&self.inner.backward(input, grad_output))
+    }
+
+    fn pass_state(&self) -> Option<PassState> {
+        self.inner.pass_state()
+    }
+
+    fn set_pass_state(&mut self, state: Option<PassState>) {
+        self.inner.set_pass_state(state);
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        self.inner.parameters()
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.inner.parameters_mut()
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        self.inner.gradients()
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.inner.gradients_mut()
+    }
+
+    fn gradient_rows(&self) -> Vec<Option<Vec<usize>>> {
+        self.inner.gradient_rows()
+    }
+
+    fn buffers(&self) -> NamedTensors<'_, T> {
+        self.inner.buffers()
+    }
+
+    fn buffers_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.inner.buffers_mut()
+    }
+
+    fn zero_grad(&mut self) {
+        self.inner.zero_grad();
+    }
+
+    fn regularization_loss(&self) -> T {
+        self.inner.regularization_loss()
+    }
+
+    fn apply_constraints(&mut self) {
+        self.inner.apply_constraints();
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.inner.set_training(training);
+    }
+
+    fn name(&self) -> String {
+        format!("Residual({})", self.inner.name())
+    }
+}
+
+/// Layers that all receive the same input, shared by `Parallel` and `Concat`.
+/// Parameters are named `<branch index>.<parameter name>`.
+struct Branches<T> {
+    layers: Vec<Box<dyn TrainableLayer<T>>>,
+}
+
+impl<T> Branches<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn forward(&self, input: &Tensor<T>) -> Result<Vec<Tensor<T>>, String> {
+        if self.layers.is_empty() {
+            return Err("Container has no branches".into());
+        }
+        self.layers.iter().map(|layer| layer.forward(input, None)).collect()
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>) -> Result<Vec<Tensor<T>>, String> {
+        if self.layers.is_empty() {
+            return Err("Container has no branches".into());
+        }
+        self.layers.iter_mut().map(|layer| layer.forward_train(input, None)).collect()
+    }
+
+    /// Sum of the input gradients of every branch, given the output gradient of each
+    fn backward(&mut self, input: &Tensor<T>, grads: Vec<Tensor<T>>) -> Tensor<T> {
+        let mut total = Tensor::zeros(*input.get_accuracy(), input.get_shape().clone());
+        for (layer, grad) in self.layers.iter_mut().zip(grads) {
+            total = total.add(&layer.backward(input, &grad));
+        }
+        total
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .parameters()
+                    .into_iter()
+                    .map(move |(name, param)| (format!("{}.{}", i, name), param))
+            })
+            .collect()
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .parameters_mut()
+                    .into_iter()
+                    .map(move |(name, param)| (format!("{}.{}", i, name), param))
+            })
+            .collect()
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .gradients()
+                    .into_iter()
+                    .map(move |(name, grad)| (format!("{}.{}", i, name), grad))
+            })
+            .collect()
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .gradients_mut()
+                    .into_iter()
+                    .map(move |(name, grad)| (format!("{}.{}", i, name), grad))
+            })
+            .collect()
+    }
+
+    fn gradient_rows(&self) -> Vec<Option<Vec<usize>>> {
+        self.layers.iter().flat_map(|layer| layer.gradient_rows()).collect()
+    }
+
+    fn buffers(&self) -> NamedTensors<'_, T> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .buffers()
+                    .into_iter()
+                    .map(move |(name, buffer)| (format!("{}.{}", i, name), buffer))
+            })
+            .collect()
+    }
+
+    fn buffers_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .buffers_mut()
+                    .into_iter()
+                    .map(move |(name, buffer)| (format!("{}.{}", i, name), buffer))
+            })
+            .collect()
+    }
+
+    /// Pass state of every branch
+    fn pass_states(&self) -> Vec<Option<PassState>> {
+        self.layers.iter().map(|layer| layer.pass_state()).collect()
+    }
+
+    /// Hands every branch its own state; `None` clears them all
+    fn set_pass_states(&mut self, states: Option<&[Option<PassState>]>) {
+        for (i, layer) in self.layers.iter_mut().enumerate() {
+            layer.set_pass_state(states.and_then(|states| states.get(i).cloned().flatten()));
+        }
+    }
+
+    fn zero_grad(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.zero_grad();
+        }
+    }
+
+    fn regularization_loss(&self) -> T {
+        self.layers
+            .iter()
+            .fold(T::default(), |acc, layer| acc + layer.regularization_loss())
+    }
+
+    fn apply_constraints(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.apply_constraints();
+        }
+    }
+
+    fn set_training(&mut self, training: bool) {
+        for layer in self.layers.iter_mut() {
+            layer.set_training(training);
+        }
+    }
+
+    fn names(&self) -> String {
+        self.layers.iter().map(|layer| layer.name()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Runs every branch on the same input and sums their outputs, which must share one shape.
+pub struct Parallel<T> {
+    branches: Branches<T>,
+}
+
+impl<T> Parallel<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    pub fn new() -> Self {
+        Parallel {
+            branches: Branches { layers: Vec::new() },
+        }
+    }
+
+    pub fn with_branch(mut self, layer: impl TrainableLayer<T> + 'static) -> Self {
+        self.branches.layers.push(Box::new(layer));
+        self
+    }
+
+    fn merge(outputs: Vec<Tensor<T>>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let mut outputs = outputs.into_iter();
+        let mut total = outputs.next().unwrap();
+        for output in outputs {
+            if output.get_shape() != total.get_shape() {
+                return Err("Parallel branches must produce outputs of the same shape".into());
+            }
+            total = total.add(&output);
+        }
+        Ok(activate(total, activation))
+    }
+}
+
+impl<T> Default for Parallel<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TrainableLayer<T> for Parallel<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        Self::merge(self.branches.forward(input)?, activation)
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        Self::merge(self.branches.forward_train(input)?, activation)
+    }
+
+    /// Every branch receives the full output gradient
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let grads = vec![grad_output.clone(); self.branches.layers.len()];
+        self.branches.backward(input, grads)
+    }
+
+    fn pass_state(&self) -> Option<PassState> {
+        Some(Rc::new(self.branches.pass_states()))
+    }
+
+    fn set_pass_state(&mut self, state: Option<PassState>) {
+        let states = state.and_then(|state| state.downcast::<Vec<Option<PassState>>>().ok());
+        self.branches.set_pass_states(states.as_deref().map(Vec::as_slice));
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        self.branches.parameters()
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.branches.parameters_mut()
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        self.branches.gradients()
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.branches.gradients_mut()
+    }
+
+    fn gradient_rows(&self) -> Vec<Option<Vec<usize>>> {
+        self.branches.gradient_rows()
+    }
+
+    fn buffers(&self) -> NamedTensors<'_, T> {
+        self.branches.buffers()
+    }
+
+    fn buffers_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.branches.buffers_mut()
+    }
+
+    fn zero_grad(&mut self) {
+        self.branches.zero_grad();
+    }
+
+    fn regularization_loss(&self) -> T {
+        self.branches.regularization_loss()
+    }
+
+    fn apply_constraints(&mut self) {
+        self.branches.apply_constraints();
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.branches.set_training(training);
+    }
+
+    fn name(&self) -> String {
+        format!("Parallel({})", self.branches.names())
+    }
+}
+
+/// Output shape and pass state of every branch in a training forward
+struct ConcatPass {
+    shapes: Vec<Vec<usize>>,
+    states: Vec<Option<PassState>>,
+}
+
+/// Runs every branch on the same input and concatenates their outputs along
+/// `axis` (the last one by default); the other dimensions must match.
+pub struct Concat<T> {
+    branches: Branches<T>,
+    axis: Option<usize>,
+    /// Pass of the last `forward_train`
+    pass: Option<Rc<ConcatPass>>,
+}
+
+impl<T> Concat<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    pub fn new() -> Self {
+        Concat {
+            branches: Branches { layers: Vec::new() },
+            axis: None,
+            pass: None,
+        }
+    }
+
+    pub fn with_branch(mut self, layer: impl TrainableLayer<T> + 'static) -> Self {
+        self.branches.layers.push(Box::new(layer));
+        self
+    }
+
+    pub fn with_axis(mut self, axis: usize) -> Self {
+        self.axis = Some(axis);
+        self
+    }
+
+    fn axis_for(&self, rank: usize) -> usize {
+        self.axis.unwrap_or(rank.saturating_sub(1))
+    }
+
+    /// (number of blocks before `axis`, size of each branch's block)
+    fn blocks(&self, shapes: &[Vec<usize>]) -> Result<(usize, Vec<usize>), String> {
+        let rank = shapes[0].len();
+        let axis = self.axis_for(rank);
+        if rank == 0 || axis >= rank {
+            return Err(format!("Cannot concatenate outputs of rank {} along axis {}", rank, axis));
+        }
+        for shape in shapes {
+            let same_outer = shape.len() == rank
+                && (0..rank).filter(|&d| d != axis).all(|d| shape[d] == shapes[0][d]);
+            if !same_outer {
+                return Err("Concat branches must produce outputs that only differ along the concat axis".into());
+            }
+        }
+        let outer = shapes[0][..axis].iter().product();
+        let sizes = shapes.iter().map(|shape| shape[axis..].iter().product()).collect();
+        Ok((outer, sizes))
+    }
+
+    fn merge(&self, outputs: Vec<Tensor<T>>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let shapes: Vec<Vec<usize>> = outputs.iter().map(|o| o.get_shape().clone()).collect();
+        let (outer, sizes) = self.blocks(&shapes)?;
+        let axis = self.axis_for(shapes[0].len());
+
+        let mut data = Vec::with_capacity(outputs.iter().map(|o| o.get_size()).sum());
+        for block in 0..outer {
+            for (output, &size) in outputs.iter().zip(&sizes) {
+                data.extend_from_slice(&output.get_data()[block * size..(block + 1) * size]);
+            }
+        }
+        let mut shape = shapes[0].clone();
+        shape[axis] = shapes.iter().map(|s| s[axis]).sum();
+        Ok(activate(Tensor::new(*outputs[0].get_accuracy(), data, shape), activation))
+    }
+}
+
+impl<T> Default for Concat<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TrainableLayer<T> for Concat<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        self.merge(self.branches.forward(input)?, activation)
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let outputs = self.branches.forward_train(input)?;
+        let shapes = outputs.iter().map(|o| o.get_shape().clone()).collect();
+        let output = self.merge(outputs, activation)?;
+        self.pass = Some(Rc::new(ConcatPass {
+            shapes,
+            states: self.branches.pass_states(),
+        }));
+        Ok(output)
+    }
+
+    /// Each branch receives the slice of the output gradient matching its output
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let shapes = match &self.pass {
+            Some(pass) => pass.shapes.clone(),
+            // Without a training pass the branch output shapes come from a plain forward
+            None => self
+                .branches
+                .forward(input)
+                .unwrap()
+                .iter()
+                .map(|output| output.get_shape().clone())
+                .collect(),
+        };
+        let (outer, sizes) = self.blocks(&shapes).unwrap();
+        if grad_output.get_size() != outer * sizes.iter().sum::<usize>() {
+            panic!("Error: Output gradient shape does not match the Concat output");
+        }
+
+        let mut grads: Vec<Vec<T>> = sizes.iter().map(|&size| Vec::with_capacity(outer * size)).collect();
+        let mut offset = 0;
+        for _ in 0..outer {
+            for (grad, &size) in grads.iter_mut().zip(&sizes) {
+                grad.extend_from_slice(&grad_output.get_data()[offset..offset + size]);
+                offset += size;
+            }
+        }
+        let accuracy = *grad_output.get_accuracy();
+        let grads = grads
+            .into_iter()
+            .zip(shapes)
+            .map(|(data, shape)| Tensor::new(accuracy, data, shape))
+            .collect();
+        self.branches.backward(input, grads)
+    }
+
+    fn pass_state(&self) -> Option<PassState> {
+        self.pass.clone().map(|pass| pass as PassState)
+    }
+
+    fn set_pass_state(&mut self, state: Option<PassState>) {
+        self.pass = state.and_then(|state| state.downcast::<ConcatPass>().ok());
+        let states = self.pass.as_ref().map(|pass| pass.states.as_slice());
+        self.branches.set_pass_states(states);
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        self.branches.parameters()
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.branches.parameters_mut()
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        self.branches.gradients()
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.branches.gradients_mut()
+    }
+
+    fn gradient_rows(&self) -> Vec<Option<Vec<usize>>> {
+        self.branches.gradient_rows()
+    }
+
+    fn buffers(&self) -> NamedTensors<'_, T> {
+        self.branches.buffers()
+    }
+
+    fn buffers_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.branches.buffers_mut()
+    }
+
+    fn zero_grad(&mut self) {
+        self.branches.zero_grad();
+    }
+
+    fn regularization_loss(&self) -> T {
+        self.branches.regularization_loss()
+    }
+
+    fn apply_constraints(&mut self) {
+        self.branches.apply_constraints();
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.branches.set_training(training);
+    }
+
+    fn name(&self) -> String {
+        format!("Concat({})", self.branches.names())
+    }
+}
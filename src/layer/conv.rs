@@ -0,0 +1,466 @@
+use std::ops::{Add, Mul, Sub};
+
+use rand::Rng;
+
+use crate::{
+    tensor::Tensor,
+    types::{Accuracy, Randomizable},
+};
+
+use crate::layer::initializer::Initializer;
+
+use super::activation::activate;
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
+
+/// Spatial layout of a convolution over one `[channels, height, width]` sample.
+/// Padding is given separately for each side so 1-D "same"/"causal" padding can reuse it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ConvGeometry {
+    pub channels: usize,
+    pub in_h: usize,
+    pub in_w: usize,
+    pub kernel: (usize, usize),
+    pub stride: (usize, usize),
+    pub dilation: (usize, usize),
+    pub pad_top: usize,
+    pub pad_left: usize,
+    pub out_h: usize,
+    pub out_w: usize,
+}
+
+impl ConvGeometry {
+    /// `pad_h` / `pad_w` are (before, after) padding of each axis.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        channels: usize,
+        in_h: usize,
+        in_w: usize,
+        kernel: (usize, usize),
+        stride: (usize, usize),
+        dilation: (usize, usize),
+        pad_h: (usize, usize),
+        pad_w: (usize, usize),
+    ) -> Result<Self, String> {
+        let span_h = dilation.0 * (kernel.0 - 1) + 1;
+        let span_w = dilation.1 * (kernel.1 - 1) + 1;
+        let padded_h = in_h + pad_h.0 + pad_h.1;
+        let padded_w = in_w + pad_w.0 + pad_w.1;
+
+        if padded_h < span_h || padded_w < span_w {
+            return Err("Input is smaller than the dilated kernel".into());
+        }
+
+        Ok(ConvGeometry {
+            channels,
+            in_h,
+            in_w,
+            kernel,
+            stride,
+            dilation,
+            pad_top: pad_h.0,
+            pad_left: pad_w.0,
+            out_h: (padded_h - span_h) / stride.0 + 1,
+            out_w: (padded_w - span_w) / stride.1 + 1,
+        })
+    }
+
+    fn patch_size(&self) -> usize {
+        self.channels * self.kernel.0 * self.kernel.1
+    }
+
+    fn locations(&self) -> usize {
+        self.out_h * self.out_w
+    }
+
+    /// Input position read by kernel tap (i, j) at output (oy, ox), `None` inside the padding
+    fn source(&self, oy: usize, ox: usize, i: usize, j: usize) -> Option<(usize, usize)> {
+        let y = (oy * self.stride.0 + i * self.dilation.0).checked_sub(self.pad_top)?;
+        let x = (ox * self.stride.1 + j * self.dilation.1).checked_sub(self.pad_left)?;
+        if y < self.in_h && x < self.in_w { Some((y, x)) } else { None }
+    }
+}
+
+/// Unfolds one `[channels, in_h, in_w]` sample into a `[channels * kh * kw, out_h * out_w]` matrix
+pub(crate) fn im2col<T: Copy + Default>(sample: &[T], geo: &ConvGeometry) -> Vec<T> {
+    let (kh, kw) = geo.kernel;
+    let locations = geo.locations();
+    let mut cols = vec![T::default(); geo.patch_size() * locations];
+
+    for c in 0..geo.channels {
+        for i in 0..kh {
+            for j in 0..kw {
+                let row = (c * kh + i) * kw + j;
+                for oy in 0..geo.out_h {
+                    for ox in 0..geo.out_w {
+                        if let Some((y, x)) = geo.source(oy, ox, i, j) {
+                            cols[row * locations + oy * geo.out_w + ox] = sample[(c * geo.in_h + y) * geo.in_w + x];
+                        }
+                    }
+                }
+            }
+        }
+    }
+    cols
+}
+
+/// Inverse of `im2col`: adds every column entry back onto the sample position it was read from
+pub(crate) fn col2im<T: Copy + Add<Output = T>>(cols: &[T], geo: &ConvGeometry, sample: &mut [T]) {
+    let (kh, kw) = geo.kernel;
+    let locations = geo.locations();
+
+    for c in 0..geo.channels {
+        for i in 0..kh {
+            for j in 0..kw {
+                let row = (c * kh + i) * kw + j;
+                for oy in 0..geo.out_h {
+                    for ox in 0..geo.out_w {
+                        if let Some((y, x)) = geo.source(oy, ox, i, j) {
+                            let idx = (c * geo.in_h + y) * geo.in_w + x;
+                            sample[idx] = sample[idx] + cols[row * locations + oy * geo.out_w + ox];
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Grouped convolution of `[batch, groups * geo.channels, in_h, in_w]` data with
+/// `[out_channels, geo.channels, kh, kw]` weights. Returns `[batch, out_channels, out_h, out_w]` data.
+pub(crate) fn conv_forward<T>(
+    input: &[T],
+    batch: usize,
+    weights: &Tensor<T>,
+    bias: &Tensor<T>,
+    geo: &ConvGeometry,
+    groups: usize,
+) -> Vec<T>
+where
+    T: Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    let accuracy = *weights.get_accuracy();
+    let out_channels = weights.get_shape()[0];
+    let group_out = out_channels / groups;
+    let patch = geo.patch_size();
+    let locations = geo.locations();
+    let sample_size = groups * geo.channels * geo.in_h * geo.in_w;
+    let group_size = geo.channels * geo.in_h * geo.in_w;
+
+    let group_weights: Vec<Tensor<T>> = (0..groups)
+        .map(|g| {
+            let rows = &weights.get_data()[g * group_out * patch..(g + 1) * group_out * patch];
+            Tensor::new(accuracy, rows.to_vec(), vec![group_out, patch])
+        })
+        .collect();
+
+    let mut output = Vec::with_capacity(batch * out_channels * locations);
+    for n in 0..batch {
+        for (g, w) in group_weights.iter().enumerate() {
+            let start = n * sample_size + g * group_size;
+            let cols = Tensor::new(
+                accuracy,
+                im2col(&input[start..start + group_size], geo),
+                vec![patch, locations],
+            );
+            let out = w.matmul(&cols);
+            for (o, row) in out.get_data().chunks(locations).enumerate() {
+                let b = bias.get_data()[g * group_out + o];
+                output.extend(row.iter().map(|&v| v + b));
+            }
+        }
+    }
+    output
+}
+
+/// Gradients of `conv_forward`: (grad_input, grad_weights, grad_bias)
+pub(crate) fn conv_backward<T>(
+    input: &[T],
+    batch: usize,
+    weights: &Tensor<T>,
+    grad_output: &[T],
+    geo: &ConvGeometry,
+    groups: usize,
+) -> (Vec<T>, Vec<T>, Vec<T>)
+where
+    T: Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    let accuracy = *weights.get_accuracy();
+    let out_channels = weights.get_shape()[0];
+    let group_out = out_channels / groups;
+    let patch = geo.patch_size();
+    let locations = geo.locations();
+    let sample_size = groups * geo.channels * geo.in_h * geo.in_w;
+    let group_size = geo.channels * geo.in_h * geo.in_w;
+
+    let mut grad_input = vec![T::default(); input.len()];
+    let mut grad_weights = vec![T::default(); weights.get_size()];
+    let mut grad_bias = vec![T::default(); out_channels];
+
+    for g in 0..groups {
+        let w_start = g * group_out * patch;
+        let w = Tensor::new(
+            accuracy,
+            weights.get_data()[w_start..w_start + group_out * patch].to_vec(),
+            vec![group_out, patch],
+        );
+        let w_t = w.transpose();
+
+        for n in 0..batch {
+            let start = n * sample_size + g * group_size;
+            let cols = Tensor::new(
+                accuracy,
+                im2col(&input[start..start + group_size], geo),
+                vec![patch, locations],
+            );
+
+            let out_start = (n * out_channels + g * group_out) * locations;
+            let grad_out = Tensor::new(
+                accuracy,
+                grad_output[out_start..out_start + group_out * locations].to_vec(),
+                vec![group_out, locations],
+            );
+
+            // dW = dY * colsᵗ
+            let grad_w = grad_out.matmul(&cols.transpose());
+            for (acc, &v) in grad_weights[w_start..w_start + group_out * patch].iter_mut().zip(grad_w.get_data()) {
+                *acc = *acc + v;
+            }
+
+            // db = sum(dY) over locations
+            let grad_b = grad_out.sum(1);
+            for (acc, &v) in grad_bias[g * group_out..(g + 1) * group_out].iter_mut().zip(grad_b.get_data()) {
+                *acc = *acc + v;
+            }
+
+            // dX = col2im(Wᵗ * dY)
+            let grad_cols = w_t.matmul(&grad_out);
+            col2im(grad_cols.get_data(), geo, &mut grad_input[start..start + group_size]);
+        }
+    }
+
+    (grad_input, grad_weights, grad_bias)
+}
+
+/// Options of a `Conv2D` layer. Every tuple is (height, width).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Conv2DConfig {
+    pub kernel_size: (usize, usize),
+    pub stride: (usize, usize),
+    /// Zeros added on both sides of each spatial axis
+    pub padding: (usize, usize),
+    pub dilation: (usize, usize),
+    /// Input and output channels are split into this many independent groups
+    pub groups: usize,
+}
+
+impl Conv2DConfig {
+    /// Stride 1, no padding, no dilation and a single group
+    pub fn new(kernel_size: (usize, usize)) -> Self {
+        Conv2DConfig {
+            kernel_size,
+            stride: (1, 1),
+            padding: (0, 0),
+            dilation: (1, 1),
+            groups: 1,
+        }
+    }
+
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: (usize, usize)) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_dilation(mut self, dilation: (usize, usize)) -> Self {
+        self.dilation = dilation;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: usize) -> Self {
+        self.groups = groups;
+        self
+    }
+}
+
+/// 2-D convolution over `[batch, channels, height, width]` (NCHW) input.
+///
+/// Weights have shape `[out_channels, in_channels / groups, kh, kw]` and bias `[out_channels]`.
+pub struct Conv2D<T> {
+    in_channels: usize,
+    config: Conv2DConfig,
+    weights: Tensor<T>,
+    bias: Tensor<T>,
+    grad_weights: Tensor<T>,
+    grad_bias: Tensor<T>,
+}
+
+impl<T> Conv2D<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    pub fn new(in_channels: usize, out_channels: usize, config: Conv2DConfig, accuracy: Accuracy) -> Conv2D<T> {
+        Self::with_initializers(
+            in_channels,
+            out_channels,
+            config,
+            accuracy,
+            &Initializer::default(),
+            &Initializer::Zeros,
+        )
+    }
+
+    pub fn with_initializers(
+        in_channels: usize,
+        out_channels: usize,
+        config: Conv2DConfig,
+        accuracy: Accuracy,
+        weight_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+    ) -> Conv2D<T> {
+        crate::rng::with_rng(|rng| {
+            Self::with_initializers_and_rng(in_channels, out_channels, config, accuracy, weight_init, bias_init, rng)
+        })
+    }
+
+    pub fn with_initializers_and_rng<R: Rng + ?Sized>(
+        in_channels: usize,
+        out_channels: usize,
+        config: Conv2DConfig,
+        accuracy: Accuracy,
+        weight_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+        rng: &mut R,
+    ) -> Conv2D<T> {
+        let (kh, kw) = config.kernel_size;
+        if config.groups == 0
+            || !in_channels.is_multiple_of(config.groups)
+            || !out_channels.is_multiple_of(config.groups)
+        {
+            panic!("Error: Channels must be divisible by the number of groups");
+        }
+        if kh == 0 || kw == 0 || config.stride.0 == 0 || config.stride.1 == 0 {
+            panic!("Error: Kernel size and stride must be positive");
+        }
+        if config.dilation.0 == 0 || config.dilation.1 == 0 {
+            panic!("Error: Dilation must be positive");
+        }
+
+        let group_in = in_channels / config.groups;
+        let weight_shape = vec![out_channels, group_in, kh, kw];
+        let fan_in = group_in * kh * kw;
+        let fan_out = out_channels * kh * kw;
+
+        let weights = weight_init.initialize_with_rng(weight_shape.clone(), fan_in, fan_out, accuracy, rng);
+        let bias = bias_init.initialize_with_rng(vec![out_channels], fan_in, fan_out, accuracy, rng);
+
+        Conv2D {
+            in_channels,
+            config,
+            grad_weights: Tensor::zeros(accuracy, weight_shape),
+            grad_bias: Tensor::zeros(accuracy, vec![out_channels]),
+            weights,
+            bias,
+        }
+    }
+}
+
+impl<T> Conv2D<T>
+where
+    T: Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    pub fn get_weights(&self) -> &Tensor<T> {
+        &self.weights
+    }
+
+    pub fn get_bias(&self) -> &Tensor<T> {
+        &self.bias
+    }
+
+    pub fn get_config(&self) -> &Conv2DConfig {
+        &self.config
+    }
+
+    fn geometry(&self, input: &Tensor<T>) -> Result<ConvGeometry, String> {
+        let shape = input.get_shape();
+        if shape.len() != 4 {
+            return Err("Input tensor must be 4D [batch, channels, height, width]".into());
+        }
+        if shape[1] != self.in_channels {
+            return Err("Input channels do not match the layer".into());
+        }
+
+        let c = &self.config;
+        ConvGeometry::new(
+            self.in_channels / c.groups,
+            shape[2],
+            shape[3],
+            c.kernel_size,
+            c.stride,
+            c.dilation,
+            (c.padding.0, c.padding.0),
+            (c.padding.1, c.padding.1),
+        )
+    }
+}
+
+impl<T> TrainableLayer<T> for Conv2D<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let geo = self.geometry(input)?;
+        let batch = input.get_shape()[0];
+        let out_channels = self.weights.get_shape()[0];
+
+        let data = conv_forward(input.get_data(), batch, &self.weights, &self.bias, &geo, self.config.groups);
+        let output = Tensor::new(*input.get_accuracy(), data, vec![batch, out_channels, geo.out_h, geo.out_w]);
+
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let geo = self.geometry(input).unwrap();
+        let batch = input.get_shape()[0];
+        let out_channels = self.weights.get_shape()[0];
+        if grad_output.get_shape() != &vec![batch, out_channels, geo.out_h, geo.out_w] {
+            panic!("Error: Output gradient shape does not match the convolution output");
+        }
+
+        let (grad_input, grad_w, grad_b) = conv_backward(
+            input.get_data(),
+            batch,
+            &self.weights,
+            grad_output.get_data(),
+            &geo,
+            self.config.groups,
+        );
+
+        let accuracy = *input.get_accuracy();
+        self.grad_weights = self
+            .grad_weights
+            .add(&Tensor::new(accuracy, grad_w, self.weights.get_shape().clone()));
+        self.grad_bias = self.grad_bias.add(&Tensor::new(accuracy, grad_b, vec![out_channels]));
+
+        Tensor::new(accuracy, grad_input, input.get_shape().clone())
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![("weights".into(), &self.weights), ("bias".into(), &self.bias)]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("weights".into(), &mut self.weights), ("bias".into(), &mut self.bias)]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![("weights".into(), &self.grad_weights), ("bias".into(), &self.grad_bias)]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("weights".into(), &mut self.grad_weights), ("bias".into(), &mut self.grad_bias)]
+    }
+}
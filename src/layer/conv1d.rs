@@ -0,0 +1,238 @@
+use std::ops::{Add, Mul, Sub};
+
+use rand::Rng;
+
+use crate::{
+    tensor::Tensor,
+    types::{Accuracy, Randomizable},
+};
+
+use crate::layer::conv::{conv_backward, conv_forward, ConvGeometry};
+use crate::layer::initializer::Initializer;
+
+use super::activation::activate;
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
+
+/// Padding mode of a `Conv1D` layer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Conv1DPadding {
+    /// No padding: only full windows are used
+    Valid,
+    /// Zeros split over both ends so the output length is `ceil(length / stride)`
+    Same,
+    /// Zeros on the left only, so output `t` never sees inputs after `t`
+    Causal,
+}
+
+/// Options of a `Conv1D` layer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Conv1DConfig {
+    pub kernel_size: usize,
+    pub stride: usize,
+    pub padding: Conv1DPadding,
+    pub dilation: usize,
+}
+
+impl Conv1DConfig {
+    /// Stride 1, valid padding and no dilation
+    pub fn new(kernel_size: usize) -> Self {
+        Conv1DConfig {
+            kernel_size,
+            stride: 1,
+            padding: Conv1DPadding::Valid,
+            dilation: 1,
+        }
+    }
+
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Conv1DPadding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_dilation(mut self, dilation: usize) -> Self {
+        self.dilation = dilation;
+        self
+    }
+
+    /// Zeros added (before, after) a sequence of `length` steps
+    fn pad(&self, length: usize) -> (usize, usize) {
+        let span = self.dilation * (self.kernel_size - 1) + 1;
+        match self.padding {
+            Conv1DPadding::Valid => (0, 0),
+            Conv1DPadding::Causal => (span - 1, 0),
+            Conv1DPadding::Same => {
+                let out = length.div_ceil(self.stride);
+                let total = ((out.max(1) - 1) * self.stride + span).saturating_sub(length);
+                (total / 2, total - total / 2)
+            }
+        }
+    }
+}
+
+/// 1-D convolution over `[batch, channels, length]` input.
+///
+/// Weights have shape `[out_channels, in_channels, kernel_size]` and bias `[out_channels]`.
+pub struct Conv1D<T> {
+    in_channels: usize,
+    config: Conv1DConfig,
+    weights: Tensor<T>,
+    bias: Tensor<T>,
+    grad_weights: Tensor<T>,
+    grad_bias: Tensor<T>,
+}
+
+impl<T> Conv1D<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    pub fn new(in_channels: usize, out_channels: usize, config: Conv1DConfig, accuracy: Accuracy) -> Conv1D<T> {
+        Self::with_initializers(
+            in_channels,
+            out_channels,
+            config,
+            accuracy,
+            &Initializer::default(),
+            &Initializer::Zeros,
+        )
+    }
+
+    pub fn with_initializers(
+        in_channels: usize,
+        out_channels: usize,
+        config: Conv1DConfig,
+        accuracy: Accuracy,
+        weight_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+    ) -> Conv1D<T> {
+        crate::rng::with_rng(|rng| {
+            Self::with_initializers_and_rng(in_channels, out_channels, config, accuracy, weight_init, bias_init, rng)
+        })
+    }
+
+    pub fn with_initializers_and_rng<R: Rng + ?Sized>(
+        in_channels: usize,
+        out_channels: usize,
+        config: Conv1DConfig,
+        accuracy: Accuracy,
+        weight_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+        rng: &mut R,
+    ) -> Conv1D<T> {
+        if config.kernel_size == 0 || config.stride == 0 || config.dilation == 0 {
+            panic!("Error: Kernel size, stride and dilation must be positive");
+        }
+
+        let weight_shape = vec![out_channels, in_channels, config.kernel_size];
+        let fan_in = in_channels * config.kernel_size;
+        let fan_out = out_channels * config.kernel_size;
+
+        let weights = weight_init.initialize_with_rng(weight_shape.clone(), fan_in, fan_out, accuracy, rng);
+        let bias = bias_init.initialize_with_rng(vec![out_channels], fan_in, fan_out, accuracy, rng);
+
+        Conv1D {
+            in_channels,
+            config,
+            grad_weights: Tensor::zeros(accuracy, weight_shape),
+            grad_bias: Tensor::zeros(accuracy, vec![out_channels]),
+            weights,
+            bias,
+        }
+    }
+}
+
+impl<T> Conv1D<T>
+where
+    T: Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    pub fn get_weights(&self) -> &Tensor<T> {
+        &self.weights
+    }
+
+    pub fn get_bias(&self) -> &Tensor<T> {
+        &self.bias
+    }
+
+    pub fn get_config(&self) -> &Conv1DConfig {
+        &self.config
+    }
+
+    /// The sequence is handled as a `[channels, 1, length]` image
+    fn geometry(&self, input: &Tensor<T>) -> Result<ConvGeometry, String> {
+        let shape = input.get_shape();
+        if shape.len() != 3 {
+            return Err("Input tensor must be 3D [batch, channels, length]".into());
+        }
+        if shape[1] != self.in_channels {
+            return Err("Input channels do not match the layer".into());
+        }
+
+        let c = &self.config;
+        ConvGeometry::new(
+            self.in_channels,
+            1,
+            shape[2],
+            (1, c.kernel_size),
+            (1, c.stride),
+            (1, c.dilation),
+            (0, 0),
+            c.pad(shape[2]),
+        )
+    }
+}
+
+impl<T> TrainableLayer<T> for Conv1D<T>
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let geo = self.geometry(input)?;
+        let batch = input.get_shape()[0];
+        let out_channels = self.weights.get_shape()[0];
+
+        let data = conv_forward(input.get_data(), batch, &self.weights, &self.bias, &geo, 1);
+        let output = Tensor::new(*input.get_accuracy(), data, vec![batch, out_channels, geo.out_w]);
+
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let geo = self.geometry(input).unwrap();
+        let batch = input.get_shape()[0];
+        let out_channels = self.weights.get_shape()[0];
+        if grad_output.get_shape() != &vec![batch, out_channels, geo.out_w] {
+            panic!("Error: Output gradient shape does not match the convolution output");
+        }
+
+        let (grad_input, grad_w, grad_b) =
+            conv_backward(input.get_data(), batch, &self.weights, grad_output.get_data(), &geo, 1);
+
+        let accuracy = *input.get_accuracy();
+        self.grad_weights = self
+            .grad_weights
+            .add(&Tensor::new(accuracy, grad_w, self.weights.get_shape().clone()));
+        self.grad_bias = self.grad_bias.add(&Tensor::new(accuracy, grad_b, vec![out_channels]));
+
+        Tensor::new(accuracy, grad_input, input.get_shape().clone())
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![("weights".into(), &self.weights), ("bias".into(), &self.bias)]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("weights".into(), &mut self.weights), ("bias".into(), &mut self.bias)]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![("weights".into(), &self.grad_weights), ("bias".into(), &self.grad_bias)]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("weights".into(), &mut self.grad_weights), ("bias".into(), &mut self.grad_bias)]
+    }
+}
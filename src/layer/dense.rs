@@ -1,17 +1,31 @@
 use std::ops::{Add, Mul, Sub};
 
+use num_traits::Float;
+use rand::Rng;
+
 use crate::{
     tensor::Tensor,
     types::{Accuracy, Randomizable},
 };
 
 use crate::layer::activation::ActivationFn;
+use crate::layer::initializer::Initializer;
+use crate::layer::regularizer::{Constraint, Regularizer};
+
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
 
-use super::trainable::TrainableLayer;
+/// (grad_input, grad_weights, grad_bias) returned by the inherent `DenseLayer::backward`
+pub type DenseGradients<T> = (Tensor<T>, Tensor<T>, Tensor<T>);
 
 pub struct DenseLayer<T> {
     weights: Tensor<T>,
     bias: Tensor<T>,
+    grad_weights: Tensor<T>,
+    grad_bias: Tensor<T>,
+    kernel_regularizer: Option<Regularizer<T>>,
+    bias_regularizer: Option<Regularizer<T>>,
+    kernel_constraint: Option<Constraint<T>>,
+    bias_constraint: Option<Constraint<T>>,
 }
 
 impl<T> DenseLayer<T>
@@ -19,16 +33,87 @@ where
     T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
 {
     pub fn new(input_size: usize, output_size: usize, accuracy: Accuracy) -> DenseLayer<T> {
-        let weight_data: Vec<T> = (0..input_size * output_size)
-            .map(|_| T::random_weight())
-            .collect();
+        crate::rng::with_rng(|rng| Self::new_with_rng(input_size, output_size, accuracy, rng))
+    }
 
-        let bias_data: Vec<T> = (0..output_size).map(|_| T::default()).collect();
+    /// Like `new`, drawing the initial weights from `rng`
+    pub fn new_with_rng<R: Rng + ?Sized>(
+        input_size: usize,
+        output_size: usize,
+        accuracy: Accuracy,
+        rng: &mut R,
+    ) -> DenseLayer<T> {
+        Self::with_initializers_and_rng(
+            input_size,
+            output_size,
+            accuracy,
+            &Initializer::default(),
+            &Initializer::Zeros,
+            rng,
+        )
+    }
 
-        let weights = Tensor::new(accuracy, weight_data, vec![input_size, output_size]);
-        let bias = Tensor::new(accuracy, bias_data, vec![output_size]);
+    pub fn with_initializers(
+        input_size: usize,
+        output_size: usize,
+        accuracy: Accuracy,
+        weight_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+    ) -> DenseLayer<T> {
+        crate::rng::with_rng(|rng| {
+            Self::with_initializers_and_rng(input_size, output_size, accuracy, weight_init, bias_init, rng)
+        })
+    }
+
+    pub fn with_initializers_and_rng<R: Rng + ?Sized>(
+        input_size: usize,
+        output_size: usize,
+        accuracy: Accuracy,
+        weight_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+        rng: &mut R,
+    ) -> DenseLayer<T> {
+        let weights = weight_init.initialize_with_rng(
+            vec![input_size, output_size],
+            input_size,
+            output_size,
+            accuracy,
+            rng,
+        );
+        let bias = bias_init.initialize_with_rng(vec![output_size], input_size, output_size, accuracy, rng);
+
+        DenseLayer {
+            grad_weights: Tensor::zeros(accuracy, vec![input_size, output_size]),
+            grad_bias: Tensor::zeros(accuracy, vec![output_size]),
+            kernel_regularizer: None,
+            bias_regularizer: None,
+            kernel_constraint: None,
+            bias_constraint: None,
+            weights,
+            bias,
+        }
+    }
 
-        DenseLayer { weights, bias }
+    pub fn with_kernel_regularizer(mut self, regularizer: Regularizer<T>) -> Self {
+        self.kernel_regularizer = Some(regularizer);
+        self
+    }
+
+    pub fn with_bias_regularizer(mut self, regularizer: Regularizer<T>) -> Self {
+        self.bias_regularizer = Some(regularizer);
+        self
+    }
+
+    /// Constraint applied to the weights after every update
+    pub fn with_kernel_constraint(mut self, constraint: Constraint<T>) -> Self {
+        self.kernel_constraint = Some(constraint);
+        self
+    }
+
+    /// Constraint applied to the bias after every update
+    pub fn with_bias_constraint(mut self, constraint: Constraint<T>) -> Self {
+        self.bias_constraint = Some(constraint);
+        self
     }
 
     pub fn forward(
@@ -66,26 +151,29 @@ where
     pub fn backward(
         &self,
         input: &Tensor<T>,
-        dL_dy: &Tensor<T>,
-    ) -> (Tensor<T>, Tensor<T>, Tensor<T>)
+        grad_output: &Tensor<T>,
+    ) -> DenseGradients<T>
     where
-        T: Copy
-            + Default
-            + Add<Output = T>
-            + Sub<Output = T>
-            + Mul<Output = T>
-            + From<f32>,
+        T: Float,
     {
-        // 1. Gradiente respecto a los pesos: Xᵗ * dL_dy
-        let input_T = input.transpose();
-        let grad_weights = input_T.matmul(dL_dy);
+        // 1. Gradiente respecto a los pesos: Xᵗ * grad_output
+        let input_t = input.transpose();
+        let mut grad_weights = input_t.matmul(grad_output);
     
         // 2. Gradiente respecto al bias: sum(delta) sobre axis 0
-        let grad_bias = dL_dy.sum(0);
+        let mut grad_bias = grad_output.sum(0);
+
+        // Gradiente de la regularización
+        if let Some(regularizer) = &self.kernel_regularizer {
+            grad_weights = grad_weights.add(&regularizer.gradient(&self.weights));
+        }
+        if let Some(regularizer) = &self.bias_regularizer {
+            grad_bias = grad_bias.add(&regularizer.gradient(&self.bias));
+        }
     
-        // 3. Gradiente respecto al input: dL_dy * Wᵗ
-        let weights_T = self.weights.transpose();
-        let grad_input = dL_dy.matmul(&weights_T);
+        // 3. Gradiente respecto al input: grad_output * Wᵗ
+        let weights_t = self.weights.transpose();
+        let grad_input = grad_output.matmul(&weights_t);
     
         (grad_input, grad_weights, grad_bias)
     }
@@ -122,7 +210,7 @@ where
         + std::ops::Add<Output = T>
         + std::ops::Sub<Output = T>
         + std::ops::Mul<Output = T>
-        + From<f32>
+        + Float
         + std::fmt::Debug
         + Randomizable,
 {
@@ -131,13 +219,46 @@ where
     }
     
 
-    fn backward(&self, input: &Tensor<T>, grad_output: &Tensor<T>) -> (Tensor<T>, Tensor<T>, Tensor<T>) {
-        self.backward(input, grad_output)
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let (grad_input, grad_w, grad_b) = DenseLayer::backward(self, input, grad_output);
+        self.grad_weights = self.grad_weights.add(&grad_w);
+        self.grad_bias = self.grad_bias.add(&grad_b);
+        grad_input
     }
 
-    fn update_params(&mut self, grad_w: &Tensor<T>, grad_b: &Tensor<T>, learning_rate: T) {
-        self.weights = self.weights.sub(&grad_w.scale(learning_rate));
-        self.bias = self.bias.sub(&grad_b.scale(learning_rate));
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![("weights".into(), &self.weights), ("bias".into(), &self.bias)]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("weights".into(), &mut self.weights), ("bias".into(), &mut self.bias)]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![("weights".into(), &self.grad_weights), ("bias".into(), &self.grad_bias)]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("weights".into(), &mut self.grad_weights), ("bias".into(), &mut self.grad_bias)]
     }
-}
 
+    fn regularization_loss(&self) -> T {
+        let mut penalty = T::zero();
+        if let Some(regularizer) = &self.kernel_regularizer {
+            penalty = penalty + regularizer.penalty(&self.weights);
+        }
+        if let Some(regularizer) = &self.bias_regularizer {
+            penalty = penalty + regularizer.penalty(&self.bias);
+        }
+        penalty
+    }
+
+    fn apply_constraints(&mut self) {
+        if let Some(constraint) = &self.kernel_constraint {
+            self.weights = constraint.apply(&self.weights);
+        }
+        if let Some(constraint) = &self.bias_constraint {
+            self.bias = constraint.apply(&self.bias);
+        }
+    }
+}
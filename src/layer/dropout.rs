@@ -0,0 +1,224 @@
+use std::rc::Rc;
+
+use num_traits::Float;
+use rand::Rng;
+
+use crate::tensor::Tensor;
+
+use super::activation::activate;
+use super::trainable::{PassState, TrainableLayer};
+
+/// `-scale * alpha` of SELU, the value dropped units are set to by `AlphaDropout`
+const ALPHA_PRIME: f64 = -1.758_099_340_847_376_6;
+
+fn validate_rate(rate: f32) {
+    if !(0.0..1.0).contains(&rate) {
+        panic!("Error: Dropout rate must be in [0, 1)");
+    }
+}
+
+/// Keeps each of `size` units with probability `1 - rate`, drawing from the crate RNG
+pub(crate) fn keep_mask(size: usize, rate: f32) -> Vec<bool> {
+    crate::rng::with_rng(|rng| (0..size).map(|_| rng.random::<f32>() >= rate).collect())
+}
+
+/// Multiplies `grad_output` by the mask of the pass being backpropagated; a pass
+/// without a mask (inference mode or rate 0) was the identity
+fn apply_mask<T>(mask: Option<&[T]>, active: bool, grad_output: &Tensor<T>) -> Tensor<T>
+where
+    T: Float + Default,
+{
+    match mask {
+        Some(mask) if mask.len() == grad_output.get_size() => {
+            let data = grad_output.get_data().iter().zip(mask).map(|(&g, &m)| g * m).collect();
+            Tensor::new(*grad_output.get_accuracy(), data, grad_output.get_shape().clone())
+        }
+        Some(_) => panic!("Error: Output gradient size does not match the dropout mask of the pass"),
+        None if !active => grad_output.clone(),
+        None => panic!("Error: Dropout backward in training mode needs a forward_train first"),
+    }
+}
+
+/// Dropout mask carried as a pass state
+fn mask_from_state<T: 'static>(state: Option<PassState>) -> Option<Rc<Vec<T>>> {
+    state.and_then(|state| state.downcast::<Vec<T>>().ok())
+}
+
+/// Inverted dropout: in training mode every unit is zeroed with probability
+/// `rate` and the kept ones are scaled by `1 / (1 - rate)`, so inference is the identity.
+pub struct Dropout<T> {
+    rate: f32,
+    training: bool,
+    /// Factor every input was multiplied by in the last `forward_train`
+    mask: Option<Rc<Vec<T>>>,
+}
+
+impl<T> Dropout<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn new(rate: f32) -> Self {
+        validate_rate(rate);
+        Dropout {
+            rate,
+            training: false,
+            mask: None,
+        }
+    }
+
+    pub fn get_rate(&self) -> f32 {
+        self.rate
+    }
+
+    fn sample_mask(&self, size: usize) -> Vec<T> {
+        let scale = T::one() / T::from(1.0 - self.rate).unwrap();
+        keep_mask(size, self.rate)
+            .into_iter()
+            .map(|keep| if keep { scale } else { T::zero() })
+            .collect()
+    }
+
+    fn apply(&self, input: &Tensor<T>, mask: &[T]) -> Tensor<T> {
+        let data = input.get_data().iter().zip(mask).map(|(&x, &m)| x * m).collect();
+        Tensor::new(*input.get_accuracy(), data, input.get_shape().clone())
+    }
+}
+
+impl<T> TrainableLayer<T> for Dropout<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        if !self.training || self.rate == 0.0 {
+            return Ok(activate(input.clone(), activation));
+        }
+        let mask = self.sample_mask(input.get_size());
+        Ok(activate(self.apply(input, &mask), activation))
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        if !self.training || self.rate == 0.0 {
+            self.mask = None;
+            return Ok(activate(input.clone(), activation));
+        }
+        let mask = self.sample_mask(input.get_size());
+        let output = self.apply(input, &mask);
+        self.mask = Some(Rc::new(mask));
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        if grad_output.get_shape() != input.get_shape() {
+            panic!("Error: Output gradient shape does not match the Dropout output");
+        }
+        apply_mask(self.mask.as_deref().map(Vec::as_slice), self.training && self.rate > 0.0, grad_output)
+    }
+
+    fn pass_state(&self) -> Option<PassState> {
+        self.mask.clone().map(|mask| mask as PassState)
+    }
+
+    fn set_pass_state(&mut self, state: Option<PassState>) {
+        self.mask = mask_from_state(state);
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
+
+/// Dropout for self-normalising (SELU) networks: dropped units are set to the
+/// negative saturation value of SELU and the result is affinely rescaled so
+/// that inputs with zero mean and unit variance keep those statistics.
+/// The identity at inference.
+pub struct AlphaDropout<T> {
+    rate: f32,
+    training: bool,
+    /// Factor every input was multiplied by in the last `forward_train`
+    mask: Option<Rc<Vec<T>>>,
+}
+
+impl<T> AlphaDropout<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn new(rate: f32) -> Self {
+        validate_rate(rate);
+        AlphaDropout {
+            rate,
+            training: false,
+            mask: None,
+        }
+    }
+
+    pub fn get_rate(&self) -> f32 {
+        self.rate
+    }
+
+    /// Affine correction `(a, b)` applied after replacing the dropped units
+    fn affine(&self) -> (T, T) {
+        let p = self.rate as f64;
+        let q = 1.0 - p;
+        let a = (q + ALPHA_PRIME * ALPHA_PRIME * q * p).powf(-0.5);
+        let b = -a * ALPHA_PRIME * p;
+        (T::from(a).unwrap(), T::from(b).unwrap())
+    }
+
+    /// Output and the gradient factor (`a` for kept units, 0 for dropped ones)
+    fn sample(&self, input: &Tensor<T>) -> (Tensor<T>, Vec<T>) {
+        let (a, b) = self.affine();
+        let alpha = T::from(ALPHA_PRIME).unwrap();
+        let keep = keep_mask(input.get_size(), self.rate);
+
+        let data = input
+            .get_data()
+            .iter()
+            .zip(&keep)
+            .map(|(&x, &keep)| if keep { a * x + b } else { a * alpha + b })
+            .collect();
+        let mask = keep.into_iter().map(|keep| if keep { a } else { T::zero() }).collect();
+        (Tensor::new(*input.get_accuracy(), data, input.get_shape().clone()), mask)
+    }
+}
+
+impl<T> TrainableLayer<T> for AlphaDropout<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        if !self.training || self.rate == 0.0 {
+            return Ok(activate(input.clone(), activation));
+        }
+        let (output, _) = self.sample(input);
+        Ok(activate(output, activation))
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        if !self.training || self.rate == 0.0 {
+            self.mask = None;
+            return Ok(activate(input.clone(), activation));
+        }
+        let (output, mask) = self.sample(input);
+        self.mask = Some(Rc::new(mask));
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        if grad_output.get_shape() != input.get_shape() {
+            panic!("Error: Output gradient shape does not match the AlphaDropout output");
+        }
+        apply_mask(self.mask.as_deref().map(Vec::as_slice), self.training && self.rate > 0.0, grad_output)
+    }
+
+    fn pass_state(&self) -> Option<PassState> {
+        self.mask.clone().map(|mask| mask as PassState)
+    }
+
+    fn set_pass_state(&mut self, state: Option<PassState>) {
+        self.mask = mask_from_state(state);
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
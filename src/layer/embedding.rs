@@ -0,0 +1,223 @@
+use std::collections::BTreeSet;
+use std::ops::{Add, Mul, Sub};
+
+use num_traits::Float;
+use rand::Rng;
+
+use crate::layer::initializer::Initializer;
+use crate::tensor::Tensor;
+use crate::types::{Accuracy, Randomizable};
+
+use super::activation::activate;
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
+
+/// Lookup table mapping integer indices to learnable vectors.
+///
+/// The input holds indices stored as `T` values with any shape (e.g. `[batch, seq]`);
+/// the output appends the embedding dimension (`[batch, seq, dim]`). Gradients
+/// are only accumulated into the rows looked up since the last `zero_grad`, and
+/// optimizers only update those rows.
+pub struct Embedding<T> {
+    weights: Tensor<T>,
+    grad_weights: Tensor<T>,
+    touched_rows: BTreeSet<usize>,
+    padding_idx: Option<usize>,
+    max_norm: Option<T>,
+}
+
+impl<T> Embedding<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    pub fn new(num_embeddings: usize, embedding_dim: usize, accuracy: Accuracy) -> Self {
+        Self::with_initializer(num_embeddings, embedding_dim, accuracy, &Initializer::default())
+    }
+
+    pub fn with_initializer(
+        num_embeddings: usize,
+        embedding_dim: usize,
+        accuracy: Accuracy,
+        init: &Initializer<T>,
+    ) -> Self {
+        crate::rng::with_rng(|rng| Self::with_initializer_and_rng(num_embeddings, embedding_dim, accuracy, init, rng))
+    }
+
+    pub fn with_initializer_and_rng<R: Rng + ?Sized>(
+        num_embeddings: usize,
+        embedding_dim: usize,
+        accuracy: Accuracy,
+        init: &Initializer<T>,
+        rng: &mut R,
+    ) -> Self {
+        if num_embeddings == 0 || embedding_dim == 0 {
+            panic!("Error: Embedding needs at least one row and one dimension");
+        }
+        let shape = vec![num_embeddings, embedding_dim];
+
+        Embedding {
+            weights: init.initialize_with_rng(shape.clone(), num_embeddings, embedding_dim, accuracy, rng),
+            grad_weights: Tensor::zeros(accuracy, shape),
+            touched_rows: BTreeSet::new(),
+            padding_idx: None,
+            max_norm: None,
+        }
+    }
+
+    /// Index whose vector is fixed at zero and never receives gradient (e.g. padding tokens)
+    pub fn with_padding_idx(mut self, padding_idx: usize) -> Self {
+        let (rows, dim) = (self.weights.get_shape()[0], self.weights.get_shape()[1]);
+        if padding_idx >= rows {
+            panic!("Error: Padding index out of range");
+        }
+        let mut data = self.weights.get_data().clone();
+        for value in &mut data[padding_idx * dim..(padding_idx + 1) * dim] {
+            *value = T::default();
+        }
+        self.weights = Tensor::new(*self.weights.get_accuracy(), data, vec![rows, dim]);
+        self.padding_idx = Some(padding_idx);
+        self
+    }
+
+    /// Rows looked up with an L2 norm above `max_norm` are rescaled to it
+    pub fn with_max_norm(mut self, max_norm: T) -> Self {
+        self.max_norm = Some(max_norm);
+        self
+    }
+}
+
+impl<T> Embedding<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn get_weights(&self) -> &Tensor<T> {
+        &self.weights
+    }
+
+    pub fn get_padding_idx(&self) -> Option<usize> {
+        self.padding_idx
+    }
+
+    /// Rows with gradient accumulated since the last `zero_grad`
+    pub fn get_touched_rows(&self) -> Vec<usize> {
+        self.touched_rows.iter().copied().collect()
+    }
+
+    fn indices(&self, input: &Tensor<T>) -> Result<Vec<usize>, String> {
+        let rows = self.weights.get_shape()[0];
+        input
+            .get_data()
+            .iter()
+            .map(|&x| match x.to_usize() {
+                Some(idx) if idx < rows && T::from(idx).unwrap() == x => Ok(idx),
+                _ => Err(format!("Embedding index {:?} is not an integer in [0, {})", x.to_f64(), rows)),
+            })
+            .collect()
+    }
+
+    /// `row` rescaled to `max_norm` when its norm exceeds it
+    fn renorm(&self, row: &[T]) -> Vec<T> {
+        match self.max_norm {
+            Some(max_norm) => {
+                let norm = row.iter().fold(T::zero(), |acc, &x| acc + x * x).sqrt();
+                if norm > max_norm {
+                    let scale = max_norm / (norm + T::from(1e-7).unwrap());
+                    row.iter().map(|&x| x * scale).collect()
+                } else {
+                    row.to_vec()
+                }
+            }
+            None => row.to_vec(),
+        }
+    }
+
+    fn lookup(&self, input: &Tensor<T>, indices: &[usize], activation: Option<fn(T) -> T>) -> Tensor<T> {
+        let dim = self.weights.get_shape()[1];
+        let weights = self.weights.get_data();
+        let mut data = Vec::with_capacity(indices.len() * dim);
+        for &idx in indices {
+            data.extend(self.renorm(&weights[idx * dim..(idx + 1) * dim]));
+        }
+        let mut shape = input.get_shape().clone();
+        shape.push(dim);
+        let output = Tensor::new(*self.weights.get_accuracy(), data, shape);
+
+        activate(output, activation)
+    }
+}
+
+impl<T> TrainableLayer<T> for Embedding<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let indices = self.indices(input)?;
+        Ok(self.lookup(input, &indices, activation))
+    }
+
+    /// Like `forward`, but with `max_norm` the looked-up rows are renormalised in place
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let indices = self.indices(input)?;
+        if self.max_norm.is_some() {
+            let dim = self.weights.get_shape()[1];
+            for &idx in indices.iter().collect::<BTreeSet<_>>() {
+                let row = self.renorm(&self.weights.get_data()[idx * dim..(idx + 1) * dim]);
+                self.weights.get_data_mut()[idx * dim..(idx + 1) * dim].copy_from_slice(&row);
+            }
+        }
+        Ok(self.lookup(input, &indices, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let indices = self.indices(input).unwrap();
+        let dim = self.weights.get_shape()[1];
+        if grad_output.get_size() != indices.len() * dim {
+            panic!("Error: Output gradient shape does not match the Embedding output");
+        }
+
+        let grad = self.grad_weights.get_data_mut();
+        for (&idx, g) in indices.iter().zip(grad_output.get_data().chunks(dim)) {
+            if Some(idx) == self.padding_idx {
+                continue;
+            }
+            for (acc, &g) in grad[idx * dim..(idx + 1) * dim].iter_mut().zip(g) {
+                *acc = *acc + g;
+            }
+            self.touched_rows.insert(idx);
+        }
+
+        // Indices are not differentiable
+        Tensor::zeros(*input.get_accuracy(), input.get_shape().clone())
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![("weights".into(), &self.weights)]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("weights".into(), &mut self.weights)]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![("weights".into(), &self.grad_weights)]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("weights".into(), &mut self.grad_weights)]
+    }
+
+    fn gradient_rows(&self) -> Vec<Option<Vec<usize>>> {
+        vec![Some(self.get_touched_rows())]
+    }
+
+    /// Only clears the rows touched since the last call
+    fn zero_grad(&mut self) {
+        let dim = self.weights.get_shape()[1];
+        let grad = self.grad_weights.get_data_mut();
+        for &idx in &self.touched_rows {
+            for value in &mut grad[idx * dim..(idx + 1) * dim] {
+                *value = T::zero();
+            }
+        }
+        self.touched_rows.clear();
+    }
+}
@@ -0,0 +1,442 @@
+use std::ops::{Add, Mul, Sub};
+
+use num_traits::Float;
+use rand::Rng;
+
+use crate::layer::initializer::Initializer;
+use crate::tensor::Tensor;
+use crate::types::{Accuracy, Randomizable};
+
+use super::activation::{activate, sigmoid};
+use super::rnn::{recurrent_output, sequence_dims, split_output_gradient, stack_steps, time_step};
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
+
+/// Values of one step needed by the backward pass
+struct GruStep<T> {
+    reset: Vec<T>,
+    update: Vec<T>,
+    candidate: Vec<T>,
+    /// `h_{t-1} W_hn + b_hn` (reset-after) or `r * h_{t-1}` (original)
+    recurrent: Vec<T>,
+    hidden: Tensor<T>,
+}
+
+/// Places `[batch, hidden]` gate values into the columns of gate `gate` of a `[batch, 3 * hidden]` matrix
+fn gate_columns<T: Float + Default>(values: &[T], gate: usize, batch: usize, hidden: usize, accuracy: Accuracy) -> Tensor<T> {
+    let mut data = vec![T::zero(); batch * 3 * hidden];
+    for b in 0..batch {
+        let start = b * 3 * hidden + gate * hidden;
+        data[start..start + hidden].copy_from_slice(&values[b * hidden..(b + 1) * hidden]);
+    }
+    Tensor::new(accuracy, data, vec![batch, 3 * hidden])
+}
+
+/// Gated recurrent unit over `[batch, time, features]` input.
+///
+/// Gates are stored side by side in the order reset, update, candidate
+/// (`weights_ih` is `[features, 3 * hidden]`, `weights_hh` `[hidden, 3 * hidden]`):
+///
+/// - `r = sigmoid(x W_ir + h W_hr + b_r)`, `z = sigmoid(x W_iz + h W_hz + b_z)`
+/// - original: `n = tanh(x W_in + b_n + (r * h) W_hn)`
+/// - reset after (cuDNN style): `n = tanh(x W_in + b_n + r * (h W_hn + b_hn))`,
+///   which adds a separate `recurrent_bias`
+/// - `h' = (1 - z) * n + z * h`
+///
+/// Output conventions follow `LSTM`: the last state, or every state with
+/// `with_return_sequences(true)`. `forward_with_state` returns the final hidden
+/// state next to the output, and with `with_return_state(true)` every
+/// `forward_train` keeps it for `get_final_state`.
+pub struct GRU<T> {
+    weights_ih: Tensor<T>,
+    weights_hh: Tensor<T>,
+    bias: Tensor<T>,
+    recurrent_bias: Tensor<T>,
+    grad_weights_ih: Tensor<T>,
+    grad_weights_hh: Tensor<T>,
+    grad_bias: Tensor<T>,
+    grad_recurrent_bias: Tensor<T>,
+    reset_after: bool,
+    return_sequences: bool,
+    return_state: bool,
+    initial_state: Option<Tensor<T>>,
+    initial_state_grad: Option<Tensor<T>>,
+    final_state: Option<Tensor<T>>,
+    final_state_grad: Option<Tensor<T>>,
+}
+
+impl<T> GRU<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    /// Glorot-uniform input weights, orthogonal recurrent weights and zero biases
+    pub fn new(input_size: usize, hidden_size: usize, accuracy: Accuracy) -> Self {
+        Self::with_initializers(
+            input_size,
+            hidden_size,
+            accuracy,
+            &Initializer::XavierUniform,
+            &Initializer::Orthogonal { gain: 1.0 },
+            &Initializer::Zeros,
+        )
+    }
+
+    /// `bias_init` is used for both the input and the recurrent bias
+    pub fn with_initializers(
+        input_size: usize,
+        hidden_size: usize,
+        accuracy: Accuracy,
+        kernel_init: &Initializer<T>,
+        recurrent_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+    ) -> Self {
+        crate::rng::with_rng(|rng| {
+            Self::with_initializers_and_rng(
+                input_size,
+                hidden_size,
+                accuracy,
+                kernel_init,
+                recurrent_init,
+                bias_init,
+                rng,
+            )
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_initializers_and_rng<R: Rng + ?Sized>(
+        input_size: usize,
+        hidden_size: usize,
+        accuracy: Accuracy,
+        kernel_init: &Initializer<T>,
+        recurrent_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+        rng: &mut R,
+    ) -> Self {
+        if input_size == 0 || hidden_size == 0 {
+            panic!("Error: GRU sizes must be positive");
+        }
+        let gates = 3 * hidden_size;
+        let weights_ih = kernel_init.initialize_with_rng(vec![input_size, gates], input_size, gates, accuracy, rng);
+        let weights_hh = recurrent_init.initialize_with_rng(vec![hidden_size, gates], hidden_size, gates, accuracy, rng);
+        let bias = bias_init.initialize_with_rng(vec![gates], input_size, gates, accuracy, rng);
+        let recurrent_bias = bias_init.initialize_with_rng(vec![gates], hidden_size, gates, accuracy, rng);
+
+        GRU {
+            grad_weights_ih: Tensor::zeros(accuracy, vec![input_size, gates]),
+            grad_weights_hh: Tensor::zeros(accuracy, vec![hidden_size, gates]),
+            grad_bias: Tensor::zeros(accuracy, vec![gates]),
+            grad_recurrent_bias: Tensor::zeros(accuracy, vec![gates]),
+            weights_ih,
+            weights_hh,
+            bias,
+            recurrent_bias,
+            reset_after: false,
+            return_sequences: false,
+            return_state: false,
+            initial_state: None,
+            initial_state_grad: None,
+            final_state: None,
+            final_state_grad: None,
+        }
+    }
+
+    /// Apply the reset gate after the recurrent matmul (cuDNN / PyTorch variant)
+    pub fn with_reset_after(mut self, reset_after: bool) -> Self {
+        self.reset_after = reset_after;
+        self
+    }
+
+    /// Output every hidden state instead of only the last one
+    pub fn with_return_sequences(mut self, return_sequences: bool) -> Self {
+        self.return_sequences = return_sequences;
+        self
+    }
+
+    /// Keep the final hidden state of every `forward_train` for `get_final_state`
+    pub fn with_return_state(mut self, return_state: bool) -> Self {
+        self.return_state = return_state;
+        self
+    }
+}
+
+impl<T> GRU<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn get_weights_ih(&self) -> &Tensor<T> {
+        &self.weights_ih
+    }
+
+    pub fn get_weights_hh(&self) -> &Tensor<T> {
+        &self.weights_hh
+    }
+
+    pub fn get_bias(&self) -> &Tensor<T> {
+        &self.bias
+    }
+
+    /// Only used by the reset-after variant
+    pub fn get_recurrent_bias(&self) -> &Tensor<T> {
+        &self.recurrent_bias
+    }
+
+    /// Hidden state `[batch, hidden]` the sequence starts from (zeros with `None`)
+    pub fn set_initial_state(&mut self, state: Option<Tensor<T>>) {
+        if let Some(state) = &state
+            && (state.get_shape().len() != 2 || state.get_shape()[1] != self.hidden_size())
+        {
+            panic!("Error: Initial state must be a [batch, hidden] tensor");
+        }
+        self.initial_state = state;
+    }
+
+    /// Gradient of the initial state from the most recent `backward` only: unlike
+    /// the parameter gradients it is overwritten by every call, not accumulated,
+    /// and `zero_grad` leaves it untouched.
+    pub fn get_initial_state_grad(&self) -> Option<&Tensor<T>> {
+        self.initial_state_grad.as_ref()
+    }
+
+    /// Final hidden state of the last `forward_train` (only kept with `with_return_state(true)`)
+    pub fn get_final_state(&self) -> Option<&Tensor<T>> {
+        self.final_state.as_ref()
+    }
+
+    /// Gradient of the loss with respect to the final hidden state, added by every
+    /// `backward` until it is replaced; `None` (the default) when the final state
+    /// does not feed the loss
+    pub fn set_final_state_grad(&mut self, grad: Option<Tensor<T>>) {
+        self.final_state_grad = grad;
+    }
+
+    /// Output of `forward` together with the final hidden state `[batch, hidden]`
+    pub fn forward_with_state(
+        &self,
+        input: &Tensor<T>,
+        activation: Option<fn(T) -> T>,
+    ) -> Result<(Tensor<T>, Tensor<T>), String> {
+        let (_, steps) = self.steps(input)?;
+        let hidden_states: Vec<Tensor<T>> = steps.into_iter().map(|step| step.hidden).collect();
+        let final_state = hidden_states.last().unwrap().clone();
+        let output = recurrent_output(hidden_states, self.return_sequences);
+
+        Ok((activate(output, activation), final_state))
+    }
+
+    fn hidden_size(&self) -> usize {
+        self.weights_hh.get_shape()[0]
+    }
+
+    fn steps(&self, input: &Tensor<T>) -> Result<(Tensor<T>, Vec<GruStep<T>>), String> {
+        let (batch, time) = sequence_dims(input, self.weights_ih.get_shape()[0])?;
+        let hidden = self.hidden_size();
+        let accuracy = *input.get_accuracy();
+        let start = match &self.initial_state {
+            Some(state) if state.get_shape()[0] != batch => {
+                return Err("Initial state batch size does not match the input".into());
+            }
+            Some(state) => state.clone(),
+            None => Tensor::zeros(accuracy, vec![batch, hidden]),
+        };
+
+        let mut steps: Vec<GruStep<T>> = Vec::with_capacity(time);
+        for t in 0..time {
+            let h_prev = steps.last().map_or(&start, |step| &step.hidden);
+            let a = time_step(input, t).matmul(&self.weights_ih).add(&self.bias);
+            let mut u = h_prev.matmul(&self.weights_hh);
+            if self.reset_after {
+                u = u.add(&self.recurrent_bias);
+            }
+            let (a, u) = (a.get_data(), u.get_data());
+
+            let size = batch * hidden;
+            let mut reset = Vec::with_capacity(size);
+            let mut update = Vec::with_capacity(size);
+            for b in 0..batch {
+                let row = b * 3 * hidden;
+                for j in 0..hidden {
+                    reset.push(sigmoid(a[row + j] + u[row + j]));
+                    update.push(sigmoid(a[row + hidden + j] + u[row + hidden + j]));
+                }
+            }
+
+            // Recurrent contribution to the candidate, before the tanh
+            let (recurrent, candidate_hh): (Vec<T>, Vec<T>) = if self.reset_after {
+                let u_n: Vec<T> = (0..size).map(|k| u[(k / hidden) * 3 * hidden + 2 * hidden + k % hidden]).collect();
+                let contribution = u_n.iter().zip(&reset).map(|(&u, &r)| r * u).collect();
+                (u_n, contribution)
+            } else {
+                let reset_hidden: Vec<T> = reset.iter().zip(h_prev.get_data()).map(|(&r, &h)| r * h).collect();
+                let projected = Tensor::new(accuracy, reset_hidden.clone(), vec![batch, hidden]).matmul(&self.weights_hh);
+                let projected = projected.get_data();
+                let contribution = (0..size)
+                    .map(|k| projected[(k / hidden) * 3 * hidden + 2 * hidden + k % hidden])
+                    .collect();
+                (reset_hidden, contribution)
+            };
+
+            let mut candidate = Vec::with_capacity(size);
+            let mut h = Vec::with_capacity(size);
+            for k in 0..size {
+                let a_n = a[(k / hidden) * 3 * hidden + 2 * hidden + k % hidden];
+                let n = (a_n + candidate_hh[k]).tanh();
+                let z = update[k];
+                candidate.push(n);
+                h.push((T::one() - z) * n + z * h_prev.get_data()[k]);
+            }
+
+            steps.push(GruStep {
+                reset,
+                update,
+                candidate,
+                recurrent,
+                hidden: Tensor::new(accuracy, h, vec![batch, hidden]),
+            });
+        }
+        Ok((start, steps))
+    }
+}
+
+impl<T> TrainableLayer<T> for GRU<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        Ok(self.forward_with_state(input, activation)?.0)
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let (output, final_state) = self.forward_with_state(input, activation)?;
+        self.final_state = self.return_state.then_some(final_state);
+        Ok(output)
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let (start, steps) = self.steps(input).unwrap();
+        let (batch, time) = (input.get_shape()[0], input.get_shape()[1]);
+        let hidden = self.hidden_size();
+        let size = batch * hidden;
+        let accuracy = *input.get_accuracy();
+        let step_grads = split_output_gradient(grad_output, self.return_sequences, batch, time, hidden);
+
+        let zeros = Tensor::zeros(accuracy, vec![batch, hidden]);
+        let mut grad_h = match &self.final_state_grad {
+            Some(grad) if grad.get_shape() != zeros.get_shape() => {
+                panic!("Error: Final state gradient must be a [batch, hidden] tensor matching the input")
+            }
+            Some(grad) => grad.clone(),
+            None => zeros.clone(),
+        };
+        let weights_ih_t = self.weights_ih.transpose();
+        let weights_hh_t = self.weights_hh.transpose();
+        let mut grad_inputs = vec![zeros; time];
+
+        for t in (0..time).rev() {
+            if let Some(grad) = &step_grads[t] {
+                grad_h = grad_h.add(grad);
+            }
+            let step = &steps[t];
+            let h_prev = if t > 0 { &steps[t - 1].hidden } else { &start };
+
+            let mut grad_candidate = Vec::with_capacity(size); // before the tanh
+            let mut grad_update = Vec::with_capacity(size); // before the sigmoid
+            let mut grad_h_prev = Vec::with_capacity(size);
+            for k in 0..size {
+                let (z, n, dh) = (step.update[k], step.candidate[k], grad_h.get_data()[k]);
+                grad_candidate.push(dh * (T::one() - z) * (T::one() - n * n));
+                grad_update.push(dh * (h_prev.get_data()[k] - n) * z * (T::one() - z));
+                grad_h_prev.push(dh * z);
+            }
+
+            // Gradient reaching the reset gate, and the candidate's path into weights_hh
+            let grad_reset_out: Vec<T>;
+            let grad_u_n: Tensor<T>;
+            if self.reset_after {
+                grad_reset_out = grad_candidate.iter().zip(&step.recurrent).map(|(&g, &u)| g * u).collect();
+                let scaled: Vec<T> = grad_candidate.iter().zip(&step.reset).map(|(&g, &r)| g * r).collect();
+                grad_u_n = gate_columns(&scaled, 2, batch, hidden, accuracy);
+            } else {
+                let grad_n_only = gate_columns(&grad_candidate, 2, batch, hidden, accuracy);
+                let grad_reset_hidden = grad_n_only.matmul(&weights_hh_t);
+                grad_reset_out = grad_reset_hidden.get_data().iter().zip(h_prev.get_data()).map(|(&g, &h)| g * h).collect();
+                for (k, value) in grad_h_prev.iter_mut().enumerate() {
+                    *value = *value + grad_reset_hidden.get_data()[k] * step.reset[k];
+                }
+                let reset_hidden = Tensor::new(accuracy, step.recurrent.clone(), vec![batch, hidden]);
+                self.grad_weights_hh = self.grad_weights_hh.add(&reset_hidden.transpose().matmul(&grad_n_only));
+                grad_u_n = Tensor::zeros(accuracy, vec![batch, 3 * hidden]);
+            }
+            let grad_reset: Vec<T> = grad_reset_out
+                .iter()
+                .zip(&step.reset)
+                .map(|(&g, &r)| g * r * (T::one() - r))
+                .collect();
+
+            let grad_rz = gate_columns(&grad_reset, 0, batch, hidden, accuracy)
+                .add(&gate_columns(&grad_update, 1, batch, hidden, accuracy));
+            let grad_a = grad_rz.add(&gate_columns(&grad_candidate, 2, batch, hidden, accuracy));
+            let grad_u = grad_rz.add(&grad_u_n);
+
+            self.grad_weights_ih = self.grad_weights_ih.add(&time_step(input, t).transpose().matmul(&grad_a));
+            self.grad_bias = self.grad_bias.add(&grad_a.sum(0));
+            self.grad_weights_hh = self.grad_weights_hh.add(&h_prev.transpose().matmul(&grad_u));
+            if self.reset_after {
+                self.grad_recurrent_bias = self.grad_recurrent_bias.add(&grad_u.sum(0));
+            }
+
+            grad_inputs[t] = grad_a.matmul(&weights_ih_t);
+            grad_h = Tensor::new(accuracy, grad_h_prev, vec![batch, hidden]).add(&grad_u.matmul(&weights_hh_t));
+        }
+
+        self.initial_state_grad = Some(grad_h);
+        stack_steps(&grad_inputs)
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        let mut params: NamedTensors<'_, T> = vec![
+            ("weights_ih".into(), &self.weights_ih),
+            ("weights_hh".into(), &self.weights_hh),
+            ("bias".into(), &self.bias),
+        ];
+        if self.reset_after {
+            params.push(("recurrent_bias".into(), &self.recurrent_bias));
+        }
+        params
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        let mut params: NamedTensorsMut<'_, T> = vec![
+            ("weights_ih".into(), &mut self.weights_ih),
+            ("weights_hh".into(), &mut self.weights_hh),
+            ("bias".into(), &mut self.bias),
+        ];
+        if self.reset_after {
+            params.push(("recurrent_bias".into(), &mut self.recurrent_bias));
+        }
+        params
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        let mut grads: NamedTensors<'_, T> = vec![
+            ("weights_ih".into(), &self.grad_weights_ih),
+            ("weights_hh".into(), &self.grad_weights_hh),
+            ("bias".into(), &self.grad_bias),
+        ];
+        if self.reset_after {
+            grads.push(("recurrent_bias".into(), &self.grad_recurrent_bias));
+        }
+        grads
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        let mut grads: NamedTensorsMut<'_, T> = vec![
+            ("weights_ih".into(), &mut self.grad_weights_ih),
+            ("weights_hh".into(), &mut self.grad_weights_hh),
+            ("bias".into(), &mut self.grad_bias),
+        ];
+        if self.reset_after {
+            grads.push(("recurrent_bias".into(), &mut self.grad_recurrent_bias));
+        }
+        grads
+    }
+}
@@ -0,0 +1,148 @@
+use std::ops::{Add, Mul, Sub};
+
+use rand::Rng;
+
+use crate::{
+    tensor::Tensor,
+    types::{Accuracy, Randomizable},
+};
+
+/// How the initial values of a parameter tensor are drawn.
+///
+/// `fan_in` / `fan_out` are the number of inputs feeding one output unit and the
+/// number of outputs fed by one input unit (for a dense layer: input and output size).
+#[derive(Clone)]
+pub enum Initializer<T> {
+    /// Uniform in `[-limit, limit]`. `Uniform { limit: 0.1 }` is the historical default.
+    Uniform { limit: f32 },
+    /// Glorot: uniform in `±sqrt(6 / (fan_in + fan_out))`
+    XavierUniform,
+    /// Glorot: normal with std `sqrt(2 / (fan_in + fan_out))`
+    XavierNormal,
+    /// Kaiming: uniform in `±sqrt(6 / fan_in)`
+    HeUniform,
+    /// Kaiming: normal with std `sqrt(2 / fan_in)`
+    HeNormal,
+    /// Uniform in `±sqrt(3 / fan_in)`
+    LecunUniform,
+    /// Normal with std `sqrt(1 / fan_in)`
+    LecunNormal,
+    /// Random (semi-)orthogonal matrix over `[shape[0], rest]`, scaled by `gain`
+    Orthogonal { gain: f32 },
+    /// Normal redrawn until it falls within two standard deviations of the mean
+    TruncatedNormal { mean: f32, std: f32 },
+    Constant(f32),
+    /// `T::default()` everywhere (zero for every supported type)
+    Zeros,
+    /// Copy of the given tensor, whose shape must match the parameter
+    FromTensor(Tensor<T>),
+}
+
+impl<T> Default for Initializer<T> {
+    fn default() -> Self {
+        Initializer::Uniform { limit: 0.1 }
+    }
+}
+
+impl<T> Initializer<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    /// Initializes a parameter with the crate generator (see `rng::set_seed`)
+    pub fn initialize(&self, shape: Vec<usize>, fan_in: usize, fan_out: usize, accuracy: Accuracy) -> Tensor<T> {
+        crate::rng::with_rng(|rng| self.initialize_with_rng(shape, fan_in, fan_out, accuracy, rng))
+    }
+
+    pub fn initialize_with_rng<R: Rng + ?Sized>(
+        &self,
+        shape: Vec<usize>,
+        fan_in: usize,
+        fan_out: usize,
+        accuracy: Accuracy,
+        rng: &mut R,
+    ) -> Tensor<T> {
+        let size: usize = shape.iter().product();
+        let fan_in = fan_in.max(1) as f32;
+        let fan_out = fan_out.max(1) as f32;
+
+        let values: Vec<f32> = match self {
+            Initializer::Uniform { limit } => uniform(size, *limit, rng),
+            Initializer::XavierUniform => uniform(size, (6.0 / (fan_in + fan_out)).sqrt(), rng),
+            Initializer::XavierNormal => normal(size, 0.0, (2.0 / (fan_in + fan_out)).sqrt(), rng),
+            Initializer::HeUniform => uniform(size, (6.0 / fan_in).sqrt(), rng),
+            Initializer::HeNormal => normal(size, 0.0, (2.0 / fan_in).sqrt(), rng),
+            Initializer::LecunUniform => uniform(size, (3.0 / fan_in).sqrt(), rng),
+            Initializer::LecunNormal => normal(size, 0.0, (1.0 / fan_in).sqrt(), rng),
+            Initializer::Orthogonal { gain } => orthogonal(&shape, *gain, rng),
+            Initializer::TruncatedNormal { mean, std } => (0..size)
+                .map(|_| loop {
+                    let z = standard_normal(rng);
+                    if z.abs() <= 2.0 {
+                        break mean + std * z;
+                    }
+                })
+                .collect(),
+            Initializer::Constant(value) => vec![*value; size],
+            Initializer::Zeros => return Tensor::zeros(accuracy, shape),
+            Initializer::FromTensor(tensor) => {
+                if tensor.get_shape() != &shape {
+                    panic!("Error: Initializer tensor shape does not match the parameter shape");
+                }
+                return tensor.clone();
+            }
+        };
+
+        Tensor::new(accuracy, values.into_iter().map(T::from_weight).collect(), shape)
+    }
+}
+
+fn uniform<R: Rng + ?Sized>(size: usize, limit: f32, rng: &mut R) -> Vec<f32> {
+    (0..size).map(|_| rng.random_range(-limit..=limit)).collect()
+}
+
+fn normal<R: Rng + ?Sized>(size: usize, mean: f32, std: f32, rng: &mut R) -> Vec<f32> {
+    (0..size).map(|_| mean + std * standard_normal(rng)).collect()
+}
+
+/// Box-Muller transform
+pub(crate) fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f32 {
+    // 1 - u keeps the argument of ln in (0, 1]
+    let u1: f32 = 1.0 - rng.random::<f32>();
+    let u2: f32 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+}
+
+/// Gram-Schmidt on a Gaussian matrix: rows are orthonormal when there are
+/// fewer rows than columns, columns are orthonormal otherwise.
+fn orthogonal<R: Rng + ?Sized>(shape: &[usize], gain: f32, rng: &mut R) -> Vec<f32> {
+    let rows = shape.first().copied().unwrap_or(1);
+    let cols: usize = shape.iter().skip(1).product();
+
+    // Orthonormalise `count` vectors of length `len`
+    let (count, len) = if rows < cols { (rows, cols) } else { (cols, rows) };
+    let mut vectors: Vec<Vec<f64>> = Vec::with_capacity(count);
+    while vectors.len() < count {
+        let mut v: Vec<f64> = (0..len).map(|_| standard_normal(rng) as f64).collect();
+        for u in &vectors {
+            let dot: f64 = v.iter().zip(u).map(|(a, b)| a * b).sum();
+            for (x, y) in v.iter_mut().zip(u) {
+                *x -= dot * y;
+            }
+        }
+        let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        // Retry the (practically impossible) degenerate draw
+        if norm > 1e-6 {
+            vectors.push(v.into_iter().map(|x| x / norm).collect());
+        }
+    }
+
+    let mut data = vec![0.0f32; rows * cols];
+    for (i, v) in vectors.iter().enumerate() {
+        for (j, &x) in v.iter().enumerate() {
+            // vector i is row i, or column i when there are more rows
+            let idx = if rows < cols { i * cols + j } else { j * cols + i };
+            data[idx] = gain * x as f32;
+        }
+    }
+    data
+}
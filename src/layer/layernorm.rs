@@ -0,0 +1,272 @@
+use num_traits::Float;
+
+use crate::tensor::Tensor;
+use crate::types::Accuracy;
+
+use super::activation::activate;
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
+
+/// Size of the normalised groups of `input`, checking that its trailing dims are `normalized_shape`
+fn group_size<T>(input: &Tensor<T>, normalized_shape: &[usize]) -> Result<usize, String>
+where
+    T: Float + Default,
+{
+    let shape = input.get_shape();
+    if shape.len() <= normalized_shape.len() || !shape.ends_with(normalized_shape) {
+        return Err(format!(
+            "Input shape {:?} must end with the normalized shape {:?} after the batch axis",
+            shape, normalized_shape
+        ));
+    }
+    Ok(normalized_shape.iter().product())
+}
+
+fn mean<T: Float>(values: impl Iterator<Item = T>, n: T) -> T {
+    values.fold(T::zero(), |acc, x| acc + x) / n
+}
+
+/// Normalises every sample over its trailing `normalized_shape` dims to zero
+/// mean and unit variance, then applies a learnable scale (`gamma`) and shift (`beta`).
+pub struct LayerNorm<T> {
+    normalized_shape: Vec<usize>,
+    gamma: Tensor<T>,
+    beta: Tensor<T>,
+    grad_gamma: Tensor<T>,
+    grad_beta: Tensor<T>,
+    epsilon: T,
+}
+
+impl<T> LayerNorm<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn new(normalized_shape: Vec<usize>, accuracy: Accuracy) -> Self {
+        let size: usize = normalized_shape.iter().product();
+        if normalized_shape.is_empty() || size == 0 {
+            panic!("Error: LayerNorm needs a non-empty normalized shape");
+        }
+        let zeros = Tensor::zeros(accuracy, normalized_shape.clone());
+
+        LayerNorm {
+            gamma: Tensor::new(accuracy, vec![T::one(); size], normalized_shape.clone()),
+            beta: zeros.clone(),
+            grad_gamma: zeros.clone(),
+            grad_beta: zeros,
+            normalized_shape,
+            epsilon: T::from(1e-5).unwrap(),
+        }
+    }
+
+    /// Added to the variance before the square root (1e-5 by default)
+    pub fn with_epsilon(mut self, epsilon: T) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn get_gamma(&self) -> &Tensor<T> {
+        &self.gamma
+    }
+
+    pub fn get_beta(&self) -> &Tensor<T> {
+        &self.beta
+    }
+
+    /// Normalised input and `1 / sqrt(var + eps)` of every group
+    fn normalize(&self, input: &Tensor<T>) -> Result<(Vec<T>, Vec<T>), String> {
+        let size = group_size(input, &self.normalized_shape)?;
+        let n = T::from(size).unwrap();
+        let mut x_hat = Vec::with_capacity(input.get_size());
+        let mut inv_stds = Vec::with_capacity(input.get_size() / size);
+
+        for group in input.get_data().chunks(size) {
+            let mu = mean(group.iter().copied(), n);
+            let var = mean(group.iter().map(|&x| (x - mu) * (x - mu)), n);
+            let inv_std = T::one() / (var + self.epsilon).sqrt();
+            x_hat.extend(group.iter().map(|&x| (x - mu) * inv_std));
+            inv_stds.push(inv_std);
+        }
+        Ok((x_hat, inv_stds))
+    }
+}
+
+impl<T> TrainableLayer<T> for LayerNorm<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let (x_hat, _) = self.normalize(input)?;
+        let size = self.gamma.get_size();
+        let (gamma, beta) = (self.gamma.get_data(), self.beta.get_data());
+        let data = x_hat
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| gamma[i % size] * x + beta[i % size])
+            .collect();
+        let output = Tensor::new(*input.get_accuracy(), data, input.get_shape().clone());
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        if grad_output.get_shape() != input.get_shape() {
+            panic!("Error: Output gradient shape does not match the LayerNorm output");
+        }
+        let (x_hat, inv_stds) = self.normalize(input).unwrap();
+        let size = self.gamma.get_size();
+        let n = T::from(size).unwrap();
+        let gamma = self.gamma.get_data();
+
+        let mut grad_input = Vec::with_capacity(input.get_size());
+        let mut grad_gamma = vec![T::zero(); size];
+        let mut grad_beta = vec![T::zero(); size];
+        for ((grad, x_hat), &inv_std) in grad_output.get_data().chunks(size).zip(x_hat.chunks(size)).zip(&inv_stds) {
+            // Gradient with respect to the normalised values
+            let d_hat: Vec<T> = grad.iter().zip(gamma).map(|(&g, &w)| g * w).collect();
+            let mean_d = mean(d_hat.iter().copied(), n);
+            let mean_dx = mean(d_hat.iter().zip(x_hat).map(|(&d, &x)| d * x), n);
+            grad_input.extend(
+                d_hat
+                    .iter()
+                    .zip(x_hat)
+                    .map(|(&d, &x)| inv_std * (d - mean_d - x * mean_dx)),
+            );
+
+            for i in 0..size {
+                grad_gamma[i] = grad_gamma[i] + grad[i] * x_hat[i];
+                grad_beta[i] = grad_beta[i] + grad[i];
+            }
+        }
+
+        let accuracy = *input.get_accuracy();
+        self.grad_gamma = self.grad_gamma.add(&Tensor::new(accuracy, grad_gamma, self.normalized_shape.clone()));
+        self.grad_beta = self.grad_beta.add(&Tensor::new(accuracy, grad_beta, self.normalized_shape.clone()));
+
+        Tensor::new(accuracy, grad_input, input.get_shape().clone())
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![("gamma".into(), &self.gamma), ("beta".into(), &self.beta)]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("gamma".into(), &mut self.gamma), ("beta".into(), &mut self.beta)]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![("gamma".into(), &self.grad_gamma), ("beta".into(), &self.grad_beta)]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("gamma".into(), &mut self.grad_gamma), ("beta".into(), &mut self.grad_beta)]
+    }
+}
+
+/// Scales every sample by the inverse root mean square of its trailing
+/// `normalized_shape` dims (no mean subtraction), then by a learnable `gamma`.
+pub struct RMSNorm<T> {
+    normalized_shape: Vec<usize>,
+    gamma: Tensor<T>,
+    grad_gamma: Tensor<T>,
+    epsilon: T,
+}
+
+impl<T> RMSNorm<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn new(normalized_shape: Vec<usize>, accuracy: Accuracy) -> Self {
+        let size: usize = normalized_shape.iter().product();
+        if normalized_shape.is_empty() || size == 0 {
+            panic!("Error: RMSNorm needs a non-empty normalized shape");
+        }
+
+        RMSNorm {
+            gamma: Tensor::new(accuracy, vec![T::one(); size], normalized_shape.clone()),
+            grad_gamma: Tensor::zeros(accuracy, normalized_shape.clone()),
+            normalized_shape,
+            epsilon: T::from(1e-6).unwrap(),
+        }
+    }
+
+    /// Added to the mean square before the square root (1e-6 by default)
+    pub fn with_epsilon(mut self, epsilon: T) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn get_gamma(&self) -> &Tensor<T> {
+        &self.gamma
+    }
+
+    /// `1 / sqrt(mean(x^2) + eps)` of every group
+    fn inv_rms(&self, input: &Tensor<T>) -> Result<Vec<T>, String> {
+        let size = group_size(input, &self.normalized_shape)?;
+        let n = T::from(size).unwrap();
+        Ok(input
+            .get_data()
+            .chunks(size)
+            .map(|group| T::one() / (mean(group.iter().map(|&x| x * x), n) + self.epsilon).sqrt())
+            .collect())
+    }
+}
+
+impl<T> TrainableLayer<T> for RMSNorm<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let inv_rms = self.inv_rms(input)?;
+        let size = self.gamma.get_size();
+        let gamma = self.gamma.get_data();
+        let data = input
+            .get_data()
+            .iter()
+            .enumerate()
+            .map(|(i, &x)| x * inv_rms[i / size] * gamma[i % size])
+            .collect();
+        let output = Tensor::new(*input.get_accuracy(), data, input.get_shape().clone());
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        if grad_output.get_shape() != input.get_shape() {
+            panic!("Error: Output gradient shape does not match the RMSNorm output");
+        }
+        let inv_rms = self.inv_rms(input).unwrap();
+        let size = self.gamma.get_size();
+        let n = T::from(size).unwrap();
+        let gamma = self.gamma.get_data();
+
+        let mut grad_input = Vec::with_capacity(input.get_size());
+        let mut grad_gamma = vec![T::zero(); size];
+        for ((grad, x), &r) in grad_output.get_data().chunks(size).zip(input.get_data().chunks(size)).zip(&inv_rms) {
+            let d: Vec<T> = grad.iter().zip(gamma).map(|(&g, &w)| g * w).collect();
+            let mean_dx = mean(d.iter().zip(x).map(|(&d, &x)| d * x), n);
+            grad_input.extend(d.iter().zip(x).map(|(&d, &x)| r * (d - x * r * r * mean_dx)));
+
+            for i in 0..size {
+                grad_gamma[i] = grad_gamma[i] + grad[i] * x[i] * r;
+            }
+        }
+
+        let accuracy = *input.get_accuracy();
+        self.grad_gamma = self.grad_gamma.add(&Tensor::new(accuracy, grad_gamma, self.normalized_shape.clone()));
+
+        Tensor::new(accuracy, grad_input, input.get_shape().clone())
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![("gamma".into(), &self.gamma)]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("gamma".into(), &mut self.gamma)]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![("gamma".into(), &self.grad_gamma)]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("gamma".into(), &mut self.grad_gamma)]
+    }
+}
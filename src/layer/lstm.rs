@@ -0,0 +1,407 @@
+use std::ops::{Add, Mul, Sub};
+
+use num_traits::Float;
+use rand::Rng;
+
+use crate::layer::initializer::Initializer;
+use crate::tensor::Tensor;
+use crate::types::{Accuracy, Randomizable};
+
+use super::activation::{activate, sigmoid};
+use super::rnn::{recurrent_output, sequence_dims, split_output_gradient, stack_steps, time_step};
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
+
+/// Hidden and cell state of an `LSTM`, both `[batch, hidden]`.
+#[derive(Clone)]
+pub struct LstmState<T> {
+    pub hidden: Tensor<T>,
+    pub cell: Tensor<T>,
+}
+
+/// Values of one step needed by the backward pass (gates after their non-linearity)
+struct LstmStep<T> {
+    input_gate: Vec<T>,
+    forget_gate: Vec<T>,
+    candidate: Vec<T>,
+    output_gate: Vec<T>,
+    tanh_cell: Vec<T>,
+    hidden: Tensor<T>,
+    cell: Tensor<T>,
+}
+
+/// Long short-term memory layer over `[batch, time, features]` input.
+///
+/// The gate weights are stored side by side in the order input, forget,
+/// candidate, output: `weights_ih` is `[features, 4 * hidden]`, `weights_hh`
+/// `[hidden, 4 * hidden]` and `bias` `[4 * hidden]`.
+///
+/// Outputs the last hidden state `[batch, hidden]`, or every hidden state
+/// `[batch, time, hidden]` with `with_return_sequences(true)`. The final hidden
+/// and cell states are returned next to the output by `forward_with_state`;
+/// with `with_return_state(true)` every `forward_train` also keeps them for
+/// `get_final_state`, so they can be read through `Sequential::layer`.
+pub struct LSTM<T> {
+    weights_ih: Tensor<T>,
+    weights_hh: Tensor<T>,
+    bias: Tensor<T>,
+    grad_weights_ih: Tensor<T>,
+    grad_weights_hh: Tensor<T>,
+    grad_bias: Tensor<T>,
+    return_sequences: bool,
+    return_state: bool,
+    initial_state: Option<LstmState<T>>,
+    initial_state_grad: Option<LstmState<T>>,
+    final_state: Option<LstmState<T>>,
+    final_state_grad: Option<LstmState<T>>,
+}
+
+impl<T> LSTM<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    /// Glorot-uniform input weights, orthogonal recurrent weights and zero
+    /// bias, except for the forget gate whose bias starts at one
+    pub fn new(input_size: usize, hidden_size: usize, accuracy: Accuracy) -> Self {
+        let mut layer = Self::with_initializers(
+            input_size,
+            hidden_size,
+            accuracy,
+            &Initializer::XavierUniform,
+            &Initializer::Orthogonal { gain: 1.0 },
+            &Initializer::Zeros,
+        );
+        let mut bias = layer.bias.get_data().clone();
+        for value in &mut bias[hidden_size..2 * hidden_size] {
+            *value = T::from_weight(1.0);
+        }
+        layer.bias = Tensor::new(accuracy, bias, vec![4 * hidden_size]);
+        layer
+    }
+
+    pub fn with_initializers(
+        input_size: usize,
+        hidden_size: usize,
+        accuracy: Accuracy,
+        kernel_init: &Initializer<T>,
+        recurrent_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+    ) -> Self {
+        crate::rng::with_rng(|rng| {
+            Self::with_initializers_and_rng(
+                input_size,
+                hidden_size,
+                accuracy,
+                kernel_init,
+                recurrent_init,
+                bias_init,
+                rng,
+            )
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_initializers_and_rng<R: Rng + ?Sized>(
+        input_size: usize,
+        hidden_size: usize,
+        accuracy: Accuracy,
+        kernel_init: &Initializer<T>,
+        recurrent_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+        rng: &mut R,
+    ) -> Self {
+        if input_size == 0 || hidden_size == 0 {
+            panic!("Error: LSTM sizes must be positive");
+        }
+        let gates = 4 * hidden_size;
+        let weights_ih = kernel_init.initialize_with_rng(vec![input_size, gates], input_size, gates, accuracy, rng);
+        let weights_hh = recurrent_init.initialize_with_rng(vec![hidden_size, gates], hidden_size, gates, accuracy, rng);
+        let bias = bias_init.initialize_with_rng(vec![gates], input_size, gates, accuracy, rng);
+
+        LSTM {
+            grad_weights_ih: Tensor::zeros(accuracy, vec![input_size, gates]),
+            grad_weights_hh: Tensor::zeros(accuracy, vec![hidden_size, gates]),
+            grad_bias: Tensor::zeros(accuracy, vec![gates]),
+            weights_ih,
+            weights_hh,
+            bias,
+            return_sequences: false,
+            return_state: false,
+            initial_state: None,
+            initial_state_grad: None,
+            final_state: None,
+            final_state_grad: None,
+        }
+    }
+
+    /// Output every hidden state instead of only the last one
+    pub fn with_return_sequences(mut self, return_sequences: bool) -> Self {
+        self.return_sequences = return_sequences;
+        self
+    }
+
+    /// Keep the final hidden and cell states of every `forward_train` for `get_final_state`
+    pub fn with_return_state(mut self, return_state: bool) -> Self {
+        self.return_state = return_state;
+        self
+    }
+}
+
+impl<T> LSTM<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn get_weights_ih(&self) -> &Tensor<T> {
+        &self.weights_ih
+    }
+
+    pub fn get_weights_hh(&self) -> &Tensor<T> {
+        &self.weights_hh
+    }
+
+    pub fn get_bias(&self) -> &Tensor<T> {
+        &self.bias
+    }
+
+    /// State the sequence starts from (zeros with `None`); its batch size must match the input
+    pub fn set_initial_state(&mut self, state: Option<LstmState<T>>) {
+        if let Some(state) = &state {
+            let hidden = self.hidden_size();
+            if state.hidden.get_shape().len() != 2
+                || state.hidden.get_shape()[1] != hidden
+                || state.cell.get_shape() != state.hidden.get_shape()
+            {
+                panic!("Error: Initial state must be two [batch, hidden] tensors");
+            }
+        }
+        self.initial_state = state;
+    }
+
+    /// Gradient of the initial state from the most recent `backward` only: unlike
+    /// the parameter gradients it is overwritten by every call, not accumulated,
+    /// and `zero_grad` leaves it untouched.
+    pub fn get_initial_state_grad(&self) -> Option<&LstmState<T>> {
+        self.initial_state_grad.as_ref()
+    }
+
+    /// Final state of the last `forward_train` (only kept with `with_return_state(true)`)
+    pub fn get_final_state(&self) -> Option<&LstmState<T>> {
+        self.final_state.as_ref()
+    }
+
+    /// Gradient of the loss with respect to the final state, added by every
+    /// `backward` until it is replaced; `None` (the default) when the final
+    /// state does not feed the loss
+    pub fn set_final_state_grad(&mut self, grad: Option<LstmState<T>>) {
+        self.final_state_grad = grad;
+    }
+
+    /// Output of `forward` together with the final hidden and cell states
+    pub fn forward_with_state(
+        &self,
+        input: &Tensor<T>,
+        activation: Option<fn(T) -> T>,
+    ) -> Result<(Tensor<T>, LstmState<T>), String> {
+        let (_, steps) = self.steps(input)?;
+        let last = steps.last().unwrap();
+        let final_state = LstmState {
+            hidden: last.hidden.clone(),
+            cell: last.cell.clone(),
+        };
+        let hidden_states = steps.into_iter().map(|step| step.hidden).collect();
+        let output = recurrent_output(hidden_states, self.return_sequences);
+
+        Ok((activate(output, activation), final_state))
+    }
+
+    fn hidden_size(&self) -> usize {
+        self.weights_hh.get_shape()[0]
+    }
+
+    fn start_state(&self, input: &Tensor<T>, batch: usize) -> Result<LstmState<T>, String> {
+        match &self.initial_state {
+            Some(state) if state.hidden.get_shape()[0] != batch => {
+                Err("Initial state batch size does not match the input".into())
+            }
+            Some(state) => Ok(state.clone()),
+            None => {
+                let zeros = Tensor::zeros(*input.get_accuracy(), vec![batch, self.hidden_size()]);
+                Ok(LstmState {
+                    hidden: zeros.clone(),
+                    cell: zeros,
+                })
+            }
+        }
+    }
+
+    fn steps(&self, input: &Tensor<T>) -> Result<(LstmState<T>, Vec<LstmStep<T>>), String> {
+        let (batch, time) = sequence_dims(input, self.weights_ih.get_shape()[0])?;
+        let start = self.start_state(input, batch)?;
+        let hidden = self.hidden_size();
+        let accuracy = *input.get_accuracy();
+
+        let mut steps: Vec<LstmStep<T>> = Vec::with_capacity(time);
+        for t in 0..time {
+            let (h_prev, c_prev) = match steps.last() {
+                Some(step) => (&step.hidden, &step.cell),
+                None => (&start.hidden, &start.cell),
+            };
+            let z = time_step(input, t)
+                .matmul(&self.weights_ih)
+                .add(&h_prev.matmul(&self.weights_hh))
+                .add(&self.bias);
+            let z = z.get_data();
+
+            let size = batch * hidden;
+            let mut input_gate = Vec::with_capacity(size);
+            let mut forget_gate = Vec::with_capacity(size);
+            let mut candidate = Vec::with_capacity(size);
+            let mut output_gate = Vec::with_capacity(size);
+            let mut tanh_cell = Vec::with_capacity(size);
+            let mut h = Vec::with_capacity(size);
+            let mut c = Vec::with_capacity(size);
+            for b in 0..batch {
+                let row = b * 4 * hidden;
+                for j in 0..hidden {
+                    let i = sigmoid(z[row + j]);
+                    let f = sigmoid(z[row + hidden + j]);
+                    let g = z[row + 2 * hidden + j].tanh();
+                    let o = sigmoid(z[row + 3 * hidden + j]);
+                    let cell = f * c_prev.get_data()[b * hidden + j] + i * g;
+
+                    input_gate.push(i);
+                    forget_gate.push(f);
+                    candidate.push(g);
+                    output_gate.push(o);
+                    tanh_cell.push(cell.tanh());
+                    c.push(cell);
+                    h.push(o * cell.tanh());
+                }
+            }
+            steps.push(LstmStep {
+                input_gate,
+                forget_gate,
+                candidate,
+                output_gate,
+                tanh_cell,
+                hidden: Tensor::new(accuracy, h, vec![batch, hidden]),
+                cell: Tensor::new(accuracy, c, vec![batch, hidden]),
+            });
+        }
+        Ok((start, steps))
+    }
+}
+
+impl<T> TrainableLayer<T> for LSTM<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        Ok(self.forward_with_state(input, activation)?.0)
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let (output, final_state) = self.forward_with_state(input, activation)?;
+        self.final_state = self.return_state.then_some(final_state);
+        Ok(output)
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let (start, steps) = self.steps(input).unwrap();
+        let (batch, time) = (input.get_shape()[0], input.get_shape()[1]);
+        let hidden = self.hidden_size();
+        let accuracy = *input.get_accuracy();
+        let step_grads = split_output_gradient(grad_output, self.return_sequences, batch, time, hidden);
+
+        let zeros = Tensor::zeros(accuracy, vec![batch, hidden]);
+        let (mut grad_h, mut grad_c) = match &self.final_state_grad {
+            Some(grad) => {
+                if grad.hidden.get_shape() != zeros.get_shape() || grad.cell.get_shape() != zeros.get_shape() {
+                    panic!("Error: Final state gradient must be two [batch, hidden] tensors matching the input");
+                }
+                (grad.hidden.clone(), grad.cell.clone())
+            }
+            None => (zeros.clone(), zeros.clone()),
+        };
+        let weights_ih_t = self.weights_ih.transpose();
+        let weights_hh_t = self.weights_hh.transpose();
+        let mut grad_inputs = vec![zeros; time];
+
+        for t in (0..time).rev() {
+            if let Some(grad) = &step_grads[t] {
+                grad_h = grad_h.add(grad);
+            }
+            let step = &steps[t];
+            let (h_prev, c_prev) = if t > 0 {
+                (&steps[t - 1].hidden, &steps[t - 1].cell)
+            } else {
+                (&start.hidden, &start.cell)
+            };
+
+            let mut grad_z = vec![T::zero(); batch * 4 * hidden];
+            let mut grad_c_prev = Vec::with_capacity(batch * hidden);
+            for b in 0..batch {
+                for j in 0..hidden {
+                    let k = b * hidden + j;
+                    let (i, f, g, o) = (step.input_gate[k], step.forget_gate[k], step.candidate[k], step.output_gate[k]);
+                    let tanh_cell = step.tanh_cell[k];
+                    let dh = grad_h.get_data()[k];
+                    let dc = grad_c.get_data()[k] + dh * o * (T::one() - tanh_cell * tanh_cell);
+
+                    let row = b * 4 * hidden;
+                    grad_z[row + j] = dc * g * i * (T::one() - i);
+                    grad_z[row + hidden + j] = dc * c_prev.get_data()[k] * f * (T::one() - f);
+                    grad_z[row + 2 * hidden + j] = dc * i * (T::one() - g * g);
+                    grad_z[row + 3 * hidden + j] = dh * tanh_cell * o * (T::one() - o);
+                    grad_c_prev.push(dc * f);
+                }
+            }
+            let grad_z = Tensor::new(accuracy, grad_z, vec![batch, 4 * hidden]);
+
+            self.grad_weights_ih = self.grad_weights_ih.add(&time_step(input, t).transpose().matmul(&grad_z));
+            self.grad_weights_hh = self.grad_weights_hh.add(&h_prev.transpose().matmul(&grad_z));
+            self.grad_bias = self.grad_bias.add(&grad_z.sum(0));
+
+            grad_inputs[t] = grad_z.matmul(&weights_ih_t);
+            grad_h = grad_z.matmul(&weights_hh_t);
+            grad_c = Tensor::new(accuracy, grad_c_prev, vec![batch, hidden]);
+        }
+
+        self.initial_state_grad = Some(LstmState {
+            hidden: grad_h,
+            cell: grad_c,
+        });
+        stack_steps(&grad_inputs)
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![
+            ("weights_ih".into(), &self.weights_ih),
+            ("weights_hh".into(), &self.weights_hh),
+            ("bias".into(), &self.bias),
+        ]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![
+            ("weights_ih".into(), &mut self.weights_ih),
+            ("weights_hh".into(), &mut self.weights_hh),
+            ("bias".into(), &mut self.bias),
+        ]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![
+            ("weights_ih".into(), &self.grad_weights_ih),
+            ("weights_hh".into(), &self.grad_weights_hh),
+            ("bias".into(), &self.grad_bias),
+        ]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![
+            ("weights_ih".into(), &mut self.grad_weights_ih),
+            ("weights_hh".into(), &mut self.grad_weights_hh),
+            ("bias".into(), &mut self.grad_bias),
+        ]
+    }
+}
@@ -1,3 +1,20 @@
 pub mod activation;
+pub mod attention;
+pub mod batchnorm;
+pub mod container;
+pub mod conv;
+pub mod conv1d;
 pub mod dense;
-pub mod trainable;
\ No newline at end of file
+pub mod dropout;
+pub mod embedding;
+pub mod gru;
+pub mod initializer;
+pub mod layernorm;
+pub mod lstm;
+pub mod pooling;
+pub mod positional;
+pub mod regularizer;
+pub mod reshape;
+pub mod rnn;
+pub mod trainable;
+pub mod transformer;
\ No newline at end of file
@@ -0,0 +1,380 @@
+use std::ops::{Add, Mul, Sub};
+
+use num_traits::Float;
+
+use crate::tensor::Tensor;
+
+use super::activation::activate;
+use super::trainable::TrainableLayer;
+
+/// Pooling windows over the last two axes of `[batch, channels, height, width]` data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PoolGeometry {
+    planes: usize, // batch * channels
+    in_h: usize,
+    in_w: usize,
+    kernel: (usize, usize),
+    stride: (usize, usize),
+    out_h: usize,
+    out_w: usize,
+}
+
+impl PoolGeometry {
+    fn new(shape: &[usize], kernel: (usize, usize), stride: (usize, usize)) -> Result<Self, String> {
+        if shape.len() != 4 {
+            return Err("Input tensor must be 4D [batch, channels, height, width]".into());
+        }
+        let (in_h, in_w) = (shape[2], shape[3]);
+        if in_h < kernel.0 || in_w < kernel.1 {
+            return Err("Input is smaller than the pooling window".into());
+        }
+
+        Ok(PoolGeometry {
+            planes: shape[0] * shape[1],
+            in_h,
+            in_w,
+            kernel,
+            stride,
+            out_h: (in_h - kernel.0) / stride.0 + 1,
+            out_w: (in_w - kernel.1) / stride.1 + 1,
+        })
+    }
+
+    /// Flat input indices covered by every output position, in output order
+    fn windows(&self) -> impl Iterator<Item = Vec<usize>> + '_ {
+        (0..self.planes).flat_map(move |p| {
+            (0..self.out_h).flat_map(move |oy| {
+                (0..self.out_w).map(move |ox| {
+                    let mut window = Vec::with_capacity(self.kernel.0 * self.kernel.1);
+                    for i in 0..self.kernel.0 {
+                        for j in 0..self.kernel.1 {
+                            let y = oy * self.stride.0 + i;
+                            let x = ox * self.stride.1 + j;
+                            window.push((p * self.in_h + y) * self.in_w + x);
+                        }
+                    }
+                    window
+                })
+            })
+        })
+    }
+}
+
+fn validate_window(kernel: (usize, usize), stride: (usize, usize)) {
+    if kernel.0 == 0 || kernel.1 == 0 || stride.0 == 0 || stride.1 == 0 {
+        panic!("Error: Pooling window and stride must be positive");
+    }
+}
+
+/// Index of the (first) largest value of every window
+fn argmax<T: Copy + PartialOrd>(data: &[T], windows: impl Iterator<Item = Vec<usize>>) -> Vec<usize> {
+    windows
+        .map(|window| {
+            let mut best = window[0];
+            for &idx in &window[1..] {
+                if data[idx] > data[best] {
+                    best = idx;
+                }
+            }
+            best
+        })
+        .collect()
+}
+
+/// Routes every output gradient back to the input position that produced it
+fn scatter<T>(input: &Tensor<T>, indices: &[usize], grad_output: &Tensor<T>) -> Tensor<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    if indices.len() != grad_output.get_size() {
+        panic!("Error: Output gradient shape does not match the pooling output");
+    }
+    let mut grad = vec![T::default(); input.get_size()];
+    for (&idx, &g) in indices.iter().zip(grad_output.get_data()) {
+        grad[idx] = grad[idx] + g;
+    }
+    Tensor::new(*input.get_accuracy(), grad, input.get_shape().clone())
+}
+
+/// Max pooling over `[batch, channels, height, width]` input.
+pub struct MaxPool2D {
+    kernel_size: (usize, usize),
+    stride: (usize, usize),
+}
+
+impl MaxPool2D {
+    /// Non-overlapping windows (stride equal to the window size)
+    pub fn new(kernel_size: (usize, usize)) -> Self {
+        validate_window(kernel_size, kernel_size);
+        MaxPool2D {
+            kernel_size,
+            stride: kernel_size,
+        }
+    }
+
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        validate_window(self.kernel_size, stride);
+        self.stride = stride;
+        self
+    }
+}
+
+impl MaxPool2D {
+    fn pool<T>(&self, input: &Tensor<T>) -> Result<(Tensor<T>, Vec<usize>), String>
+    where
+        T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd,
+    {
+        let geo = PoolGeometry::new(input.get_shape(), self.kernel_size, self.stride)?;
+        let indices = argmax(input.get_data(), geo.windows());
+        let data = indices.iter().map(|&idx| input.get_data()[idx]).collect();
+        let shape = vec![input.get_shape()[0], input.get_shape()[1], geo.out_h, geo.out_w];
+        Ok((Tensor::new(*input.get_accuracy(), data, shape), indices))
+    }
+}
+
+impl<T> TrainableLayer<T> for MaxPool2D
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let (output, _) = self.pool(input)?;
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        // The argmax is recomputed from `input`, so backward matches whichever pass produced it
+        let (_, indices) = self.pool(input).unwrap();
+        scatter(input, &indices, grad_output)
+    }
+}
+
+/// Average pooling over `[batch, channels, height, width]` input.
+pub struct AvgPool2D {
+    kernel_size: (usize, usize),
+    stride: (usize, usize),
+}
+
+impl AvgPool2D {
+    /// Non-overlapping windows (stride equal to the window size)
+    pub fn new(kernel_size: (usize, usize)) -> Self {
+        validate_window(kernel_size, kernel_size);
+        AvgPool2D {
+            kernel_size,
+            stride: kernel_size,
+        }
+    }
+
+    pub fn with_stride(mut self, stride: (usize, usize)) -> Self {
+        validate_window(self.kernel_size, stride);
+        self.stride = stride;
+        self
+    }
+}
+
+impl<T> TrainableLayer<T> for AvgPool2D
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let geo = PoolGeometry::new(input.get_shape(), self.kernel_size, self.stride)?;
+        let scale = T::one() / T::from(self.kernel_size.0 * self.kernel_size.1).unwrap();
+        let data = geo
+            .windows()
+            .map(|window| window.iter().fold(T::zero(), |acc, &idx| acc + input.get_data()[idx]) * scale)
+            .collect();
+        let shape = vec![input.get_shape()[0], input.get_shape()[1], geo.out_h, geo.out_w];
+        Ok(activate(Tensor::new(*input.get_accuracy(), data, shape), activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let geo = PoolGeometry::new(input.get_shape(), self.kernel_size, self.stride).unwrap();
+        if geo.planes * geo.out_h * geo.out_w != grad_output.get_size() {
+            panic!("Error: Output gradient shape does not match the pooling output");
+        }
+
+        let scale = T::one() / T::from(self.kernel_size.0 * self.kernel_size.1).unwrap();
+        let mut grad = vec![T::zero(); input.get_size()];
+        for (window, &g) in geo.windows().zip(grad_output.get_data()) {
+            for idx in window {
+                grad[idx] = grad[idx] + g * scale;
+            }
+        }
+        Tensor::new(*input.get_accuracy(), grad, input.get_shape().clone())
+    }
+}
+
+/// `[batch, channels, length]` viewed as a `[batch, channels, 1, length]` image
+fn as_image<T>(input: &Tensor<T>) -> Result<Tensor<T>, String>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let shape = input.get_shape();
+    if shape.len() != 3 {
+        return Err("Input tensor must be 3D [batch, channels, length]".into());
+    }
+    Ok(input.reshape(vec![shape[0], shape[1], 1, shape[2]]))
+}
+
+fn from_image<T>(output: Tensor<T>) -> Tensor<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let shape = output.get_shape().clone();
+    output.reshape(vec![shape[0], shape[1], shape[3]])
+}
+
+/// Max pooling over `[batch, channels, length]` input.
+pub struct MaxPool1D {
+    inner: MaxPool2D,
+}
+
+impl MaxPool1D {
+    pub fn new(kernel_size: usize) -> Self {
+        MaxPool1D {
+            inner: MaxPool2D::new((1, kernel_size)),
+        }
+    }
+
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.inner = self.inner.with_stride((1, stride));
+        self
+    }
+}
+
+impl<T> TrainableLayer<T> for MaxPool1D
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        Ok(from_image(self.inner.forward(&as_image(input)?, activation)?))
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        Ok(from_image(self.inner.forward_train(&as_image(input)?, activation)?))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let image = as_image(input).unwrap();
+        let shape = grad_output.get_shape();
+        let grad_output = grad_output.reshape(vec![shape[0], shape[1], 1, shape[2]]);
+        self.inner
+            .backward(&image, &grad_output)
+            .reshape(input.get_shape().clone())
+    }
+}
+
+/// Average pooling over `[batch, channels, length]` input.
+pub struct AvgPool1D {
+    inner: AvgPool2D,
+}
+
+impl AvgPool1D {
+    pub fn new(kernel_size: usize) -> Self {
+        AvgPool1D {
+            inner: AvgPool2D::new((1, kernel_size)),
+        }
+    }
+
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.inner = self.inner.with_stride((1, stride));
+        self
+    }
+}
+
+impl<T> TrainableLayer<T> for AvgPool1D
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        Ok(from_image(self.inner.forward(&as_image(input)?, activation)?))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let image = as_image(input).unwrap();
+        let shape = grad_output.get_shape();
+        let grad_output = grad_output.reshape(vec![shape[0], shape[1], 1, shape[2]]);
+        self.inner
+            .backward(&image, &grad_output)
+            .reshape(input.get_shape().clone())
+    }
+}
+
+/// (batch * channels, spatial size) of a `[batch, channels, ...]` tensor
+fn global_dims(shape: &[usize]) -> Result<(usize, usize), String> {
+    if shape.len() < 3 {
+        return Err("Input tensor must have at least 3 dimensions [batch, channels, ...]".into());
+    }
+    let spatial: usize = shape[2..].iter().product();
+    if spatial == 0 {
+        return Err("Input tensor has no spatial elements".into());
+    }
+    Ok((shape[0] * shape[1], spatial))
+}
+
+/// Averages every channel over all its spatial positions: `[batch, channels, ...]` -> `[batch, channels]`.
+pub struct GlobalAvgPool;
+
+impl<T> TrainableLayer<T> for GlobalAvgPool
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let shape = input.get_shape();
+        let (_, spatial) = global_dims(shape)?;
+        let scale = T::one() / T::from(spatial).unwrap();
+        let data = input
+            .get_data()
+            .chunks(spatial)
+            .map(|plane| plane.iter().fold(T::zero(), |acc, &x| acc + x) * scale)
+            .collect();
+        let output = Tensor::new(*input.get_accuracy(), data, vec![shape[0], shape[1]]);
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let (planes, spatial) = global_dims(input.get_shape()).unwrap();
+        if grad_output.get_size() != planes {
+            panic!("Error: Output gradient shape does not match the pooling output");
+        }
+        let scale = T::one() / T::from(spatial).unwrap();
+        let grad = grad_output
+            .get_data()
+            .iter()
+            .flat_map(|&g| std::iter::repeat_n(g * scale, spatial))
+            .collect();
+        Tensor::new(*input.get_accuracy(), grad, input.get_shape().clone())
+    }
+}
+
+/// Maximum of every channel over all its spatial positions: `[batch, channels, ...]` -> `[batch, channels]`.
+pub struct GlobalMaxPool;
+
+impl GlobalMaxPool {
+    fn pool<T>(&self, input: &Tensor<T>) -> Result<(Tensor<T>, Vec<usize>), String>
+    where
+        T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd,
+    {
+        let shape = input.get_shape();
+        let (planes, spatial) = global_dims(shape)?;
+        let windows = (0..planes).map(|p| (p * spatial..(p + 1) * spatial).collect());
+        let indices = argmax(input.get_data(), windows);
+        let data = indices.iter().map(|&idx| input.get_data()[idx]).collect();
+        Ok((Tensor::new(*input.get_accuracy(), data, vec![shape[0], shape[1]]), indices))
+    }
+}
+
+impl<T> TrainableLayer<T> for GlobalMaxPool
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let (output, _) = self.pool(input)?;
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        // The argmax is recomputed from `input`, so backward matches whichever pass produced it
+        let (_, indices) = self.pool(input).unwrap();
+        scatter(input, &indices, grad_output)
+    }
+}
@@ -0,0 +1,176 @@
+use std::ops::{Add, Mul, Sub};
+
+use num_traits::Float;
+use rand::Rng;
+
+use crate::layer::initializer::Initializer;
+use crate::tensor::Tensor;
+use crate::types::{Accuracy, Randomizable};
+
+use super::activation::activate;
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
+
+/// (batch, seq) of a `[batch, seq, d_model]` input no longer than `max_len`
+fn sequence_dims<T>(input: &Tensor<T>, max_len: usize, d_model: usize) -> Result<(usize, usize), String>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let shape = input.get_shape();
+    if shape.len() != 3 || shape[2] != d_model {
+        return Err("Input tensor must be [batch, seq, d_model]".into());
+    }
+    if shape[1] > max_len {
+        return Err(format!("Sequence length {} exceeds the maximum of {}", shape[1], max_len));
+    }
+    Ok((shape[0], shape[1]))
+}
+
+/// Adds the first `seq` rows of `table` (`[max_len, d_model]`) to every sample
+fn add_positions<T>(input: &Tensor<T>, table: &[T], activation: Option<fn(T) -> T>) -> Tensor<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let per_sample = input.get_shape()[1] * input.get_shape()[2];
+    let data = input
+        .get_data()
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| x + table[i % per_sample])
+        .collect();
+    let output = Tensor::new(*input.get_accuracy(), data, input.get_shape().clone());
+
+    activate(output, activation)
+}
+
+/// Fixed sine/cosine position signal added to `[batch, seq, d_model]` input:
+/// `PE[pos, 2i] = sin(pos / 10000^(2i / d_model))`, `PE[pos, 2i + 1] = cos(...)`.
+pub struct SinusoidalPositionalEncoding<T> {
+    table: Vec<T>,
+    max_len: usize,
+    d_model: usize,
+}
+
+impl<T> SinusoidalPositionalEncoding<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn new(max_len: usize, d_model: usize) -> Self {
+        let mut table = Vec::with_capacity(max_len * d_model);
+        for pos in 0..max_len {
+            for i in 0..d_model {
+                let rate = 10000f64.powf((i - i % 2) as f64 / d_model as f64);
+                let angle = pos as f64 / rate;
+                let value = if i.is_multiple_of(2) { angle.sin() } else { angle.cos() };
+                table.push(T::from(value).unwrap());
+            }
+        }
+
+        SinusoidalPositionalEncoding { table, max_len, d_model }
+    }
+}
+
+impl<T> TrainableLayer<T> for SinusoidalPositionalEncoding<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        sequence_dims(input, self.max_len, self.d_model)?;
+        Ok(add_positions(input, &self.table, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        if grad_output.get_shape() != input.get_shape() {
+            panic!("Error: Output gradient shape does not match the positional encoding output");
+        }
+        grad_output.clone()
+    }
+}
+
+/// Trainable position embeddings (`[max_len, d_model]`) added to `[batch, seq, d_model]` input.
+pub struct LearnedPositionalEncoding<T> {
+    embeddings: Tensor<T>,
+    grad_embeddings: Tensor<T>,
+}
+
+impl<T> LearnedPositionalEncoding<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    /// Embeddings drawn from a truncated normal with std 0.02
+    pub fn new(max_len: usize, d_model: usize, accuracy: Accuracy) -> Self {
+        Self::with_initializer(max_len, d_model, accuracy, &Initializer::TruncatedNormal { mean: 0.0, std: 0.02 })
+    }
+
+    pub fn with_initializer(max_len: usize, d_model: usize, accuracy: Accuracy, init: &Initializer<T>) -> Self {
+        crate::rng::with_rng(|rng| Self::with_initializer_and_rng(max_len, d_model, accuracy, init, rng))
+    }
+
+    pub fn with_initializer_and_rng<R: Rng + ?Sized>(
+        max_len: usize,
+        d_model: usize,
+        accuracy: Accuracy,
+        init: &Initializer<T>,
+        rng: &mut R,
+    ) -> Self {
+        if max_len == 0 || d_model == 0 {
+            panic!("Error: Positional encoding sizes must be positive");
+        }
+
+        LearnedPositionalEncoding {
+            embeddings: init.initialize_with_rng(vec![max_len, d_model], max_len, d_model, accuracy, rng),
+            grad_embeddings: Tensor::zeros(accuracy, vec![max_len, d_model]),
+        }
+    }
+}
+
+impl<T> LearnedPositionalEncoding<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn get_embeddings(&self) -> &Tensor<T> {
+        &self.embeddings
+    }
+}
+
+impl<T> TrainableLayer<T> for LearnedPositionalEncoding<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let shape = self.embeddings.get_shape();
+        sequence_dims(input, shape[0], shape[1])?;
+        Ok(add_positions(input, self.embeddings.get_data(), activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        if grad_output.get_shape() != input.get_shape() {
+            panic!("Error: Output gradient shape does not match the positional encoding output");
+        }
+
+        // Every sample adds the same rows, so their gradients are summed over the batch
+        let per_sample = input.get_shape()[1] * input.get_shape()[2];
+        let mut grad = self.grad_embeddings.get_data().clone();
+        for (i, &g) in grad_output.get_data().iter().enumerate() {
+            grad[i % per_sample] = grad[i % per_sample] + g;
+        }
+        self.grad_embeddings = Tensor::new(*input.get_accuracy(), grad, self.embeddings.get_shape().clone());
+
+        grad_output.clone()
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![("embeddings".into(), &self.embeddings)]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("embeddings".into(), &mut self.embeddings)]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![("embeddings".into(), &self.grad_embeddings)]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![("embeddings".into(), &mut self.grad_embeddings)]
+    }
+}
@@ -0,0 +1,103 @@
+use num_traits::Float;
+
+use crate::tensor::Tensor;
+
+/// Penalty on a parameter tensor, added to the loss and to its gradient.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Regularizer<T> {
+    /// `factor * sum(|x|)`
+    L1(T),
+    /// `factor * sum(x²)`
+    L2(T),
+    /// `l1 * sum(|x|) + l2 * sum(x²)`
+    ElasticNet { l1: T, l2: T },
+}
+
+impl<T> Regularizer<T>
+where
+    T: Float + Default,
+{
+    pub fn penalty(&self, param: &Tensor<T>) -> T {
+        let (l1, l2) = self.factors();
+        let mut sum_abs = T::zero();
+        let mut sum_sq = T::zero();
+        for &x in param.get_data() {
+            sum_abs = sum_abs + x.abs();
+            sum_sq = sum_sq + x * x;
+        }
+        l1 * sum_abs + l2 * sum_sq
+    }
+
+    /// Derivative of the penalty with respect to every element of `param`.
+    pub fn gradient(&self, param: &Tensor<T>) -> Tensor<T> {
+        let (l1, l2) = self.factors();
+        let two = T::one() + T::one();
+        param.map(|x| {
+            // sign(0) = 0 so untouched weights get no L1 push
+            let sign = if x > T::zero() {
+                T::one()
+            } else if x < T::zero() {
+                -T::one()
+            } else {
+                T::zero()
+            };
+            l1 * sign + two * l2 * x
+        })
+    }
+
+    fn factors(&self) -> (T, T) {
+        match *self {
+            Regularizer::L1(l1) => (l1, T::zero()),
+            Regularizer::L2(l2) => (T::zero(), l2),
+            Regularizer::ElasticNet { l1, l2 } => (l1, l2),
+        }
+    }
+}
+
+/// Projection applied to a parameter tensor after each update.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Constraint<T> {
+    /// Rescale so the L2 norm of each column (incoming weights of a unit) is at most the limit.
+    /// 1D tensors are treated as a single vector.
+    MaxNorm(T),
+    /// Clamp negative values to zero.
+    NonNeg,
+}
+
+impl<T> Constraint<T>
+where
+    T: Float + Default,
+{
+    pub fn apply(&self, param: &Tensor<T>) -> Tensor<T> {
+        match *self {
+            Constraint::NonNeg => param.map(|x| x.max(T::zero())),
+            Constraint::MaxNorm(max_norm) => {
+                let shape = param.get_shape();
+                let (rows, cols) = if shape.len() == 2 {
+                    (shape[0], shape[1])
+                } else {
+                    (param.get_size(), 1)
+                };
+
+                let data = param.get_data();
+                let mut result = data.clone();
+                for col in 0..cols {
+                    let mut sum_sq = T::zero();
+                    for row in 0..rows {
+                        let x = data[row * cols + col];
+                        sum_sq = sum_sq + x * x;
+                    }
+                    let norm = sum_sq.sqrt();
+                    if norm > max_norm {
+                        let factor = max_norm / norm;
+                        for row in 0..rows {
+                            result[row * cols + col] = data[row * cols + col] * factor;
+                        }
+                    }
+                }
+
+                Tensor::new(*param.get_accuracy(), result, shape.clone())
+            }
+        }
+    }
+}
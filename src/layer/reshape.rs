@@ -0,0 +1,74 @@
+use std::ops::{Add, Mul, Sub};
+
+use crate::tensor::Tensor;
+
+use super::activation::activate;
+use super::trainable::TrainableLayer;
+
+/// Collapses every dimension after the batch axis: `[batch, ...]` -> `[batch, features]`.
+pub struct Flatten;
+
+impl<T> TrainableLayer<T> for Flatten
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let shape = input.get_shape();
+        if shape.is_empty() {
+            return Err("Input tensor must have a batch dimension".into());
+        }
+        let features = shape[1..].iter().product();
+        let output = input.reshape(vec![shape[0], features]);
+
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        grad_output.reshape(input.get_shape().clone())
+    }
+}
+
+/// Reshapes every sample to `target_shape`, keeping the batch axis: `[batch, ...]` -> `[batch, target_shape...]`.
+pub struct Reshape {
+    target_shape: Vec<usize>,
+}
+
+impl Reshape {
+    pub fn new(target_shape: Vec<usize>) -> Self {
+        Reshape { target_shape }
+    }
+
+    pub fn get_target_shape(&self) -> &Vec<usize> {
+        &self.target_shape
+    }
+}
+
+impl<T> TrainableLayer<T> for Reshape
+where
+    T: 'static + Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let shape = input.get_shape();
+        if shape.is_empty() {
+            return Err("Input tensor must have a batch dimension".into());
+        }
+        let features: usize = shape[1..].iter().product();
+        if features != self.target_shape.iter().product::<usize>() {
+            return Err(format!(
+                "Cannot reshape samples of shape {:?} to {:?}",
+                &shape[1..],
+                self.target_shape
+            ));
+        }
+
+        let mut new_shape = vec![shape[0]];
+        new_shape.extend_from_slice(&self.target_shape);
+        let output = input.reshape(new_shape);
+
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        grad_output.reshape(input.get_shape().clone())
+    }
+}
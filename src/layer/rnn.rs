@@ -0,0 +1,373 @@
+use std::ops::{Add, Mul, Sub};
+
+use num_traits::Float;
+use rand::Rng;
+
+use crate::layer::initializer::Initializer;
+use crate::tensor::Tensor;
+use crate::types::{Accuracy, Randomizable};
+
+use super::activation::{activate, sigmoid};
+use super::trainable::{NamedTensors, NamedTensorsMut, TrainableLayer};
+
+/// Non-linearity of a recurrent layer; unlike `ActivationFn` it knows its derivative.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RecurrentActivation {
+    #[default]
+    Tanh,
+    Sigmoid,
+    Relu,
+    Linear,
+}
+
+impl RecurrentActivation {
+    pub fn apply<T: Float>(&self, x: T) -> T {
+        match self {
+            RecurrentActivation::Tanh => x.tanh(),
+            RecurrentActivation::Sigmoid => sigmoid(x),
+            RecurrentActivation::Relu => x.max(T::zero()),
+            RecurrentActivation::Linear => x,
+        }
+    }
+
+    /// Derivative expressed through the activation output `y`
+    pub fn derivative<T: Float>(&self, y: T) -> T {
+        match self {
+            RecurrentActivation::Tanh => T::one() - y * y,
+            RecurrentActivation::Sigmoid => y * (T::one() - y),
+            RecurrentActivation::Relu => {
+                if y > T::zero() {
+                    T::one()
+                } else {
+                    T::zero()
+                }
+            }
+            RecurrentActivation::Linear => T::one(),
+        }
+    }
+}
+
+/// (batch, time, features) of a `[batch, time, features]` input
+pub(crate) fn sequence_dims<T>(input: &Tensor<T>, features: usize) -> Result<(usize, usize), String>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let shape = input.get_shape();
+    if shape.len() != 3 {
+        return Err("Input tensor must be 3D [batch, time, features]".into());
+    }
+    if shape[2] != features {
+        return Err("Input features do not match the layer input size".into());
+    }
+    if shape[0] * shape[1] == 0 {
+        return Err("Input sequence is empty".into());
+    }
+    Ok((shape[0], shape[1]))
+}
+
+/// `[batch, features]` slice of a `[batch, time, features]` tensor at step `t`
+pub(crate) fn time_step<T>(sequence: &Tensor<T>, t: usize) -> Tensor<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let shape = sequence.get_shape();
+    let (batch, time, features) = (shape[0], shape[1], shape[2]);
+    let mut data = Vec::with_capacity(batch * features);
+    for b in 0..batch {
+        let start = (b * time + t) * features;
+        data.extend_from_slice(&sequence.get_data()[start..start + features]);
+    }
+    Tensor::new(*sequence.get_accuracy(), data, vec![batch, features])
+}
+
+/// Stacks `[batch, features]` steps into a `[batch, time, features]` tensor
+pub(crate) fn stack_steps<T>(steps: &[Tensor<T>]) -> Tensor<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let (batch, features) = (steps[0].get_shape()[0], steps[0].get_shape()[1]);
+    let mut data = Vec::with_capacity(batch * steps.len() * features);
+    for b in 0..batch {
+        for step in steps {
+            data.extend_from_slice(&step.get_data()[b * features..(b + 1) * features]);
+        }
+    }
+    Tensor::new(*steps[0].get_accuracy(), data, vec![batch, steps.len(), features])
+}
+
+/// Output of a recurrent layer: the last state `[batch, hidden]`, or with
+/// `return_sequences` every state `[batch, time, hidden]`
+pub(crate) fn recurrent_output<T>(mut outputs: Vec<Tensor<T>>, return_sequences: bool) -> Tensor<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    if return_sequences {
+        stack_steps(&outputs)
+    } else {
+        outputs.pop().unwrap()
+    }
+}
+
+/// Splits the gradient of a `recurrent_output` into the gradient of every
+/// step (`None` for steps not in the output)
+pub(crate) fn split_output_gradient<T>(
+    grad_output: &Tensor<T>,
+    return_sequences: bool,
+    batch: usize,
+    time: usize,
+    hidden: usize,
+) -> Vec<Option<Tensor<T>>>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let mut step_grads = vec![None; time];
+    if !return_sequences {
+        if grad_output.get_shape() != &vec![batch, hidden] {
+            panic!("Error: Output gradient shape does not match the recurrent output");
+        }
+        step_grads[time - 1] = Some(grad_output.clone());
+        return step_grads;
+    }
+
+    if grad_output.get_shape() != &vec![batch, time, hidden] {
+        panic!("Error: Output gradient shape does not match the recurrent output");
+    }
+    for (t, grad) in step_grads.iter_mut().enumerate() {
+        *grad = Some(time_step(grad_output, t));
+    }
+    step_grads
+}
+
+/// Whether the gradient of step `t` flows back into step `t - 1` under truncation after `steps`
+pub(crate) fn flows_back(t: usize, truncate: Option<usize>) -> bool {
+    match truncate {
+        Some(steps) => !t.is_multiple_of(steps),
+        None => true,
+    }
+}
+
+/// Elman recurrent layer over `[batch, time, features]` input:
+/// `h_t = activation(x_t W_ih + h_{t-1} W_hh + b)`, starting from `h_0 = 0`.
+///
+/// Outputs the last hidden state `[batch, hidden]`, or every state
+/// `[batch, time, hidden]` with `with_return_sequences(true)`.
+pub struct RNN<T> {
+    weights_ih: Tensor<T>,
+    weights_hh: Tensor<T>,
+    bias: Tensor<T>,
+    grad_weights_ih: Tensor<T>,
+    grad_weights_hh: Tensor<T>,
+    grad_bias: Tensor<T>,
+    activation: RecurrentActivation,
+    return_sequences: bool,
+    truncate: Option<usize>,
+}
+
+impl<T> RNN<T>
+where
+    T: Randomizable + Copy + Add<Output = T> + Mul<Output = T> + Default + Sub<Output = T>,
+{
+    /// Glorot-uniform input weights, orthogonal recurrent weights and zero bias
+    pub fn new(input_size: usize, hidden_size: usize, accuracy: Accuracy) -> Self {
+        Self::with_initializers(
+            input_size,
+            hidden_size,
+            accuracy,
+            &Initializer::XavierUniform,
+            &Initializer::Orthogonal { gain: 1.0 },
+            &Initializer::Zeros,
+        )
+    }
+
+    pub fn with_initializers(
+        input_size: usize,
+        hidden_size: usize,
+        accuracy: Accuracy,
+        kernel_init: &Initializer<T>,
+        recurrent_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+    ) -> Self {
+        crate::rng::with_rng(|rng| {
+            Self::with_initializers_and_rng(
+                input_size,
+                hidden_size,
+                accuracy,
+                kernel_init,
+                recurrent_init,
+                bias_init,
+                rng,
+            )
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_initializers_and_rng<R: Rng + ?Sized>(
+        input_size: usize,
+        hidden_size: usize,
+        accuracy: Accuracy,
+        kernel_init: &Initializer<T>,
+        recurrent_init: &Initializer<T>,
+        bias_init: &Initializer<T>,
+        rng: &mut R,
+    ) -> Self {
+        if input_size == 0 || hidden_size == 0 {
+            panic!("Error: RNN sizes must be positive");
+        }
+        let weights_ih =
+            kernel_init.initialize_with_rng(vec![input_size, hidden_size], input_size, hidden_size, accuracy, rng);
+        let weights_hh =
+            recurrent_init.initialize_with_rng(vec![hidden_size, hidden_size], hidden_size, hidden_size, accuracy, rng);
+        let bias = bias_init.initialize_with_rng(vec![hidden_size], input_size, hidden_size, accuracy, rng);
+
+        RNN {
+            grad_weights_ih: Tensor::zeros(accuracy, vec![input_size, hidden_size]),
+            grad_weights_hh: Tensor::zeros(accuracy, vec![hidden_size, hidden_size]),
+            grad_bias: Tensor::zeros(accuracy, vec![hidden_size]),
+            weights_ih,
+            weights_hh,
+            bias,
+            activation: RecurrentActivation::Tanh,
+            return_sequences: false,
+            truncate: None,
+        }
+    }
+
+    /// Non-linearity of the hidden state (tanh by default)
+    pub fn with_activation(mut self, activation: RecurrentActivation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    /// Output every hidden state instead of only the last one
+    pub fn with_return_sequences(mut self, return_sequences: bool) -> Self {
+        self.return_sequences = return_sequences;
+        self
+    }
+
+    /// Truncated BPTT: the sequence is split into chunks of `steps` and the
+    /// gradient does not flow across chunk boundaries (the state still does)
+    pub fn with_truncated_bptt(mut self, steps: usize) -> Self {
+        if steps == 0 {
+            panic!("Error: Truncation length must be positive");
+        }
+        self.truncate = Some(steps);
+        self
+    }
+}
+
+impl<T> RNN<T>
+where
+    T: 'static + Float + Default,
+{
+    pub fn get_weights_ih(&self) -> &Tensor<T> {
+        &self.weights_ih
+    }
+
+    pub fn get_weights_hh(&self) -> &Tensor<T> {
+        &self.weights_hh
+    }
+
+    pub fn get_bias(&self) -> &Tensor<T> {
+        &self.bias
+    }
+
+    fn hidden_size(&self) -> usize {
+        self.weights_hh.get_shape()[0]
+    }
+
+    /// Hidden states `h_1..h_T`
+    fn states(&self, input: &Tensor<T>) -> Result<Vec<Tensor<T>>, String> {
+        let (batch, time) = sequence_dims(input, self.weights_ih.get_shape()[0])?;
+        let mut h = Tensor::zeros(*input.get_accuracy(), vec![batch, self.hidden_size()]);
+        let mut states = Vec::with_capacity(time);
+        for t in 0..time {
+            let activation = self.activation;
+            h = time_step(input, t)
+                .matmul(&self.weights_ih)
+                .add(&h.matmul(&self.weights_hh))
+                .add(&self.bias)
+                .map(|x| activation.apply(x));
+            states.push(h.clone());
+        }
+        Ok(states)
+    }
+}
+
+impl<T> TrainableLayer<T> for RNN<T>
+where
+    T: 'static + Float + Default,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let output = recurrent_output(self.states(input)?, self.return_sequences);
+
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let states = self.states(input).unwrap();
+        let (batch, time) = (input.get_shape()[0], input.get_shape()[1]);
+        let hidden = self.hidden_size();
+        let accuracy = *input.get_accuracy();
+        let step_grads = split_output_gradient(grad_output, self.return_sequences, batch, time, hidden);
+
+        let weights_ih_t = self.weights_ih.transpose();
+        let weights_hh_t = self.weights_hh.transpose();
+        let zero_state = Tensor::zeros(accuracy, vec![batch, hidden]);
+        let mut grad_inputs = vec![zero_state.clone(); time];
+        let mut grad_h = zero_state.clone();
+
+        for t in (0..time).rev() {
+            if let Some(grad) = &step_grads[t] {
+                grad_h = grad_h.add(grad);
+            }
+            let activation = self.activation;
+            let grad_z = grad_h.mul_elementswise(&states[t].map(|y| activation.derivative(y)));
+            let h_prev = if t > 0 { &states[t - 1] } else { &zero_state };
+
+            self.grad_weights_ih = self.grad_weights_ih.add(&time_step(input, t).transpose().matmul(&grad_z));
+            self.grad_weights_hh = self.grad_weights_hh.add(&h_prev.transpose().matmul(&grad_z));
+            self.grad_bias = self.grad_bias.add(&grad_z.sum(0));
+
+            grad_inputs[t] = grad_z.matmul(&weights_ih_t);
+            grad_h = if flows_back(t, self.truncate) {
+                grad_z.matmul(&weights_hh_t)
+            } else {
+                zero_state.clone()
+            };
+        }
+
+        stack_steps(&grad_inputs)
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        vec![
+            ("weights_ih".into(), &self.weights_ih),
+            ("weights_hh".into(), &self.weights_hh),
+            ("bias".into(), &self.bias),
+        ]
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![
+            ("weights_ih".into(), &mut self.weights_ih),
+            ("weights_hh".into(), &mut self.weights_hh),
+            ("bias".into(), &mut self.bias),
+        ]
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        vec![
+            ("weights_ih".into(), &self.grad_weights_ih),
+            ("weights_hh".into(), &self.grad_weights_hh),
+            ("bias".into(), &self.grad_bias),
+        ]
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        vec![
+            ("weights_ih".into(), &mut self.grad_weights_ih),
+            ("weights_hh".into(), &mut self.grad_weights_hh),
+            ("bias".into(), &mut self.grad_bias),
+        ]
+    }
+}
@@ -1,19 +1,103 @@
 // src/layer/trainable.rs
 
+use std::any::Any;
+use std::rc::Rc;
+
+use crate::layer::activation::ActivationFn;
 use crate::tensor::Tensor;
 
-/// Trait para una capa entrenable individual (object-safe)
-pub trait TrainableLayer<T>: 'static
+/// Named tensors of a layer (parameters or gradients)
+pub type NamedTensors<'a, T> = Vec<(String, &'a Tensor<T>)>;
+pub type NamedTensorsMut<'a, T> = Vec<(String, &'a mut Tensor<T>)>;
+
+/// State of one `forward_train` call that `backward` needs (dropout masks, cached
+/// intermediate values, ...). It is shared, so a pass can be backpropagated more
+/// than once and several passes can be in flight.
+pub type PassState = Rc<dyn Any>;
+
+/// A single trainable layer (object-safe)
+///
+/// `parameters`, `parameters_mut`, `gradients` and `gradients_mut` return their
+/// lists in the same order, so element i of `gradients` is the gradient of
+/// element i of `parameters`. Layers without parameters keep the default
+/// (empty) implementations.
+pub trait TrainableLayer<T>: Any
 where
     T: Copy + Default + std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T>,
 {
     fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String>;
-    fn backward(&self, input: &Tensor<T>, grad_output: &Tensor<T>) -> (Tensor<T>, Tensor<T>, Tensor<T>);
-    fn update_params(&mut self, grad_w: &Tensor<T>, grad_b: &Tensor<T>, learning_rate: T);
+    /// Forward used during training; layers that keep state for `backward` override it
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        self.forward(input, activation)
+    }
+    /// Returns the gradient with respect to the input and adds the parameter gradients to the ones stored on the layer
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T>;
+
+    /// State of the last `forward_train`; `None` for layers whose `backward` only needs the input
+    fn pass_state(&self) -> Option<PassState> {
+        None
+    }
+    /// Makes the next `backward` run against the pass that produced `state`
+    fn set_pass_state(&mut self, _state: Option<PassState>) {}
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        Vec::new()
+    }
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        Vec::new()
+    }
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        Vec::new()
+    }
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        Vec::new()
+    }
+
+    /// For every entry of `gradients`, the rows (first axis) that may hold a non-zero
+    /// gradient, or `None` when any element may; optimizers only update the listed rows
+    fn gradient_rows(&self) -> Vec<Option<Vec<usize>>> {
+        self.gradients().iter().map(|_| None).collect()
+    }
+
+    /// State that is not trained by the optimizer but is part of the model, such as
+    /// BatchNorm's running statistics
+    fn buffers(&self) -> NamedTensors<'_, T> {
+        Vec::new()
+    }
+    fn buffers_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        Vec::new()
+    }
+
+    /// Resets the accumulated gradients to zero
+    fn zero_grad(&mut self) {
+        for (_, grad) in self.gradients_mut() {
+            *grad = Tensor::zeros(*grad.get_accuracy(), grad.get_shape().clone());
+        }
+    }
+
+    /// Regularization penalty added to the loss
+    fn regularization_loss(&self) -> T {
+        T::default()
+    }
+
+    /// Constraints on the parameters, applied after every update
+    fn apply_constraints(&mut self) {}
+
+    /// Switches between training (`true`) and inference (`false`) mode; only layers
+    /// that behave differently in each mode use it
+    fn set_training(&mut self, _training: bool) {}
+
+    /// Layer name for model summaries
+    fn name(&self) -> String {
+        let full = std::any::type_name::<Self>();
+        let base = full.split('<').next().unwrap_or(full);
+        base.rsplit("::").next().unwrap_or(base).to_string()
+    }
 }
 
-/// Trait para modelos secuenciales completos (como Sequential)
+/// A complete sequential model (such as Sequential)
 use crate::loss::Loss;
+use crate::model::history::TrainingHistory;
 
 pub trait TrainableModel<T, L>
 where
@@ -27,6 +111,6 @@ where
         loss_fn: &L,
         epochs: usize,
         learning_rate: T,
-        activations: &[Option<fn(T) -> T>],
-    ) -> Vec<T>;
+        activations: &[Option<ActivationFn<T>>],
+    ) -> TrainingHistory<T>;
 }
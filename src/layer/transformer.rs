@@ -0,0 +1,318 @@
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use num_traits::Float;
+
+use crate::layer::attention::MultiHeadAttention;
+use crate::layer::dense::DenseLayer;
+use crate::layer::dropout::Dropout;
+use crate::layer::initializer::Initializer;
+use crate::layer::layernorm::LayerNorm;
+use crate::tensor::Tensor;
+use crate::types::{Accuracy, Randomizable};
+
+use super::activation::activate;
+use super::trainable::{NamedTensors, NamedTensorsMut, PassState, TrainableLayer};
+
+/// Intermediate values of a training forward, reused by `backward`
+struct EncoderPass<T> {
+    attention_input: Tensor<T>,
+    attention_output: Tensor<T>,
+    norm1_input: Tensor<T>,
+    ffn_input: Tensor<T>,
+    /// First feed-forward projection before and after the ReLU, `[batch * seq, d_ff]`
+    hidden: Tensor<T>,
+    activated: Tensor<T>,
+    ffn_output: Tensor<T>,
+    norm2_input: Tensor<T>,
+    output: Tensor<T>,
+    /// Pass states of the sub-layers with per-pass randomness
+    attention_state: Option<PassState>,
+    dropout1_state: Option<PassState>,
+    dropout2_state: Option<PassState>,
+}
+
+/// Transformer encoder block over `[batch, seq, d_model]` input: multi-head
+/// self-attention and a position-wise feed-forward network (`d_model -> d_ff -> d_model`
+/// with ReLU), each wrapped in a residual connection, dropout and layer norm.
+///
+/// Post-norm (default): `x = norm1(x + attention(x))`, `x = norm2(x + ffn(x))`.
+/// Pre-norm (`with_norm_first(true)`): `x = x + attention(norm1(x))`, `x = x + ffn(norm2(x))`.
+pub struct TransformerEncoderLayer<T> {
+    attention: MultiHeadAttention<T>,
+    norm1: LayerNorm<T>,
+    norm2: LayerNorm<T>,
+    ffn1: DenseLayer<T>,
+    ffn2: DenseLayer<T>,
+    dropout1: Dropout<T>,
+    dropout2: Dropout<T>,
+    norm_first: bool,
+    /// Pass of the last `forward_train`
+    pass: Option<Rc<EncoderPass<T>>>,
+}
+
+fn relu<T: Float>(x: T) -> T {
+    x.max(T::zero())
+}
+
+/// `[batch, seq, d]` -> `[batch * seq, d]`, the 2D input the dense layers expect
+fn flat<T: Float + Default>(x: &Tensor<T>) -> Tensor<T> {
+    let shape = x.get_shape();
+    x.reshape(vec![shape[0] * shape[1], shape[2]])
+}
+
+impl<T> TransformerEncoderLayer<T>
+where
+    T: 'static + Float + Default + Debug + Randomizable,
+{
+    pub fn new(d_model: usize, num_heads: usize, d_ff: usize, accuracy: Accuracy) -> Self {
+        if d_ff == 0 {
+            panic!("Error: Feed-forward size must be positive");
+        }
+
+        TransformerEncoderLayer {
+            attention: MultiHeadAttention::new(d_model, num_heads, accuracy),
+            norm1: LayerNorm::new(vec![d_model], accuracy),
+            norm2: LayerNorm::new(vec![d_model], accuracy),
+            ffn1: DenseLayer::with_initializers(d_model, d_ff, accuracy, &Initializer::HeUniform, &Initializer::Zeros),
+            ffn2: DenseLayer::with_initializers(d_ff, d_model, accuracy, &Initializer::XavierUniform, &Initializer::Zeros),
+            dropout1: Dropout::new(0.0),
+            dropout2: Dropout::new(0.0),
+            norm_first: false,
+            pass: None,
+        }
+    }
+
+    /// Dropout rate of the attention weights and of both residual branches
+    pub fn with_dropout(mut self, rate: f32) -> Self {
+        self.attention = self.attention.with_dropout(rate);
+        self.dropout1 = Dropout::new(rate);
+        self.dropout2 = Dropout::new(rate);
+        self
+    }
+
+    /// Normalise the input of each sub-block instead of its residual sum
+    pub fn with_norm_first(mut self, norm_first: bool) -> Self {
+        self.norm_first = norm_first;
+        self
+    }
+
+    /// Causal self-attention (each position only sees earlier ones)
+    pub fn with_causal(mut self, causal: bool) -> Self {
+        self.attention = self.attention.with_causal(causal);
+        self
+    }
+
+    /// `[batch, seq]` mask of the non-padding positions, see `MultiHeadAttention::set_padding_mask`
+    pub fn set_padding_mask(&mut self, mask: Option<Tensor<T>>) {
+        self.attention.set_padding_mask(mask);
+    }
+
+    fn d_model(&self) -> usize {
+        self.norm1.get_gamma().get_size()
+    }
+
+    fn check_input(&self, input: &Tensor<T>) -> Result<(), String> {
+        let shape = input.get_shape();
+        if shape.len() != 3 || shape[2] != self.d_model() {
+            return Err("Input tensor must be [batch, seq, d_model]".into());
+        }
+        Ok(())
+    }
+
+    /// Full pass using the training forward of every sub-layer
+    fn train_pass(&mut self, input: &Tensor<T>) -> Result<EncoderPass<T>, String> {
+        self.check_input(input)?;
+        let shape = input.get_shape().clone();
+
+        let attention_input = if self.norm_first {
+            self.norm1.forward_train(input, None)?
+        } else {
+            input.clone()
+        };
+        let attention_output = self.attention.forward_train(&attention_input, None)?;
+        let residual1 = input.add(&self.dropout1.forward_train(&attention_output, None)?);
+        let (norm1_input, ffn_block_input) = if self.norm_first {
+            (input.clone(), residual1)
+        } else {
+            let normed = self.norm1.forward_train(&residual1, None)?;
+            (residual1, normed)
+        };
+
+        let ffn_input = if self.norm_first {
+            self.norm2.forward_train(&ffn_block_input, None)?
+        } else {
+            ffn_block_input.clone()
+        };
+        let hidden = self.ffn1.forward_train(&flat(&ffn_input), None)?;
+        let activated = hidden.map(relu);
+        let ffn_output = self.ffn2.forward_train(&activated, None)?.reshape(shape);
+        let residual2 = ffn_block_input.add(&self.dropout2.forward_train(&ffn_output, None)?);
+        let (norm2_input, output) = if self.norm_first {
+            (ffn_block_input, residual2)
+        } else {
+            let normed = self.norm2.forward_train(&residual2, None)?;
+            (residual2, normed)
+        };
+
+        Ok(EncoderPass {
+            attention_input,
+            attention_output,
+            norm1_input,
+            ffn_input,
+            hidden,
+            activated,
+            ffn_output,
+            norm2_input,
+            output,
+            attention_state: self.attention.pass_state(),
+            dropout1_state: self.dropout1.pass_state(),
+            dropout2_state: self.dropout2.pass_state(),
+        })
+    }
+
+    /// Gradient of the feed-forward input from that of its (pre-dropout) output
+    fn ffn_backward(&mut self, pass: &EncoderPass<T>, grad: &Tensor<T>) -> Tensor<T> {
+        let grad = self.dropout2.backward(&pass.ffn_output, grad);
+        let grad = TrainableLayer::backward(&mut self.ffn2, &pass.activated, &flat(&grad));
+        let relu_mask = pass.hidden.map(|x| if x > T::zero() { T::one() } else { T::zero() });
+        let grad = TrainableLayer::backward(&mut self.ffn1, &flat(&pass.ffn_input), &grad.mul_elementswise(&relu_mask));
+        grad.reshape(pass.output.get_shape().clone())
+    }
+
+    fn attention_backward(&mut self, pass: &EncoderPass<T>, grad: &Tensor<T>) -> Tensor<T> {
+        let grad = self.dropout1.backward(&pass.attention_output, grad);
+        self.attention.backward(&pass.attention_input, &grad)
+    }
+}
+
+impl<T> TrainableLayer<T> for TransformerEncoderLayer<T>
+where
+    T: 'static + Float + Default + Debug + Randomizable,
+{
+    fn forward(&self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        self.check_input(input)?;
+        let shape = input.get_shape().clone();
+        let ffn = |x: &Tensor<T>| -> Result<Tensor<T>, String> {
+            let hidden = self.ffn1.forward(&flat(x), None)?.map(relu);
+            let out = self.ffn2.forward(&hidden, None)?.reshape(shape.clone());
+            self.dropout2.forward(&out, None)
+        };
+
+        let output = if self.norm_first {
+            let attended = self.attention.forward(&self.norm1.forward(input, None)?, None)?;
+            let x = input.add(&self.dropout1.forward(&attended, None)?);
+            x.add(&ffn(&self.norm2.forward(&x, None)?)?)
+        } else {
+            let attended = self.attention.forward(input, None)?;
+            let x = self.norm1.forward(&input.add(&self.dropout1.forward(&attended, None)?), None)?;
+            self.norm2.forward(&x.add(&ffn(&x)?), None)?
+        };
+
+        Ok(activate(output, activation))
+    }
+
+    fn forward_train(&mut self, input: &Tensor<T>, activation: Option<fn(T) -> T>) -> Result<Tensor<T>, String> {
+        let pass = self.train_pass(input)?;
+        let output = pass.output.clone();
+        self.pass = Some(Rc::new(pass));
+
+        Ok(activate(output, activation))
+    }
+
+    fn backward(&mut self, input: &Tensor<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        // The dropout masks of the sub-layers belong to that pass, so it is never recomputed
+        let pass = match &self.pass {
+            Some(pass) if pass.output.get_shape() == input.get_shape() => Rc::clone(pass),
+            _ => panic!("Error: Encoder backward needs a forward_train of the same shape"),
+        };
+        if grad_output.get_shape() != input.get_shape() {
+            panic!("Error: Output gradient shape does not match the encoder output");
+        }
+        self.attention.set_pass_state(pass.attention_state.clone());
+        self.dropout1.set_pass_state(pass.dropout1_state.clone());
+        self.dropout2.set_pass_state(pass.dropout2_state.clone());
+
+        if self.norm_first {
+            let grad_ffn_input = self.ffn_backward(&pass, grad_output);
+            let grad_mid = grad_output.add(&self.norm2.backward(&pass.norm2_input, &grad_ffn_input));
+            let grad_attention_input = self.attention_backward(&pass, &grad_mid);
+            grad_mid.add(&self.norm1.backward(&pass.norm1_input, &grad_attention_input))
+        } else {
+            let grad_residual2 = self.norm2.backward(&pass.norm2_input, grad_output);
+            let grad_mid = grad_residual2.add(&self.ffn_backward(&pass, &grad_residual2));
+            let grad_residual1 = self.norm1.backward(&pass.norm1_input, &grad_mid);
+            grad_residual1.add(&self.attention_backward(&pass, &grad_residual1))
+        }
+    }
+
+    fn parameters(&self) -> NamedTensors<'_, T> {
+        let mut params = Vec::new();
+        for (prefix, layer) in [
+            ("attention", &self.attention as &dyn TrainableLayer<T>),
+            ("norm1", &self.norm1),
+            ("ffn1", &self.ffn1),
+            ("ffn2", &self.ffn2),
+            ("norm2", &self.norm2),
+        ] {
+            params.extend(layer.parameters().into_iter().map(|(name, p)| (format!("{}.{}", prefix, name), p)));
+        }
+        params
+    }
+
+    fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        let mut params = Vec::new();
+        for (prefix, layer) in [
+            ("attention", &mut self.attention as &mut dyn TrainableLayer<T>),
+            ("norm1", &mut self.norm1),
+            ("ffn1", &mut self.ffn1),
+            ("ffn2", &mut self.ffn2),
+            ("norm2", &mut self.norm2),
+        ] {
+            params.extend(layer.parameters_mut().into_iter().map(|(name, p)| (format!("{}.{}", prefix, name), p)));
+        }
+        params
+    }
+
+    fn gradients(&self) -> NamedTensors<'_, T> {
+        let mut grads = Vec::new();
+        for (prefix, layer) in [
+            ("attention", &self.attention as &dyn TrainableLayer<T>),
+            ("norm1", &self.norm1),
+            ("ffn1", &self.ffn1),
+            ("ffn2", &self.ffn2),
+            ("norm2", &self.norm2),
+        ] {
+            grads.extend(layer.gradients().into_iter().map(|(name, g)| (format!("{}.{}", prefix, name), g)));
+        }
+        grads
+    }
+
+    fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        let mut grads = Vec::new();
+        for (prefix, layer) in [
+            ("attention", &mut self.attention as &mut dyn TrainableLayer<T>),
+            ("norm1", &mut self.norm1),
+            ("ffn1", &mut self.ffn1),
+            ("ffn2", &mut self.ffn2),
+            ("norm2", &mut self.norm2),
+        ] {
+            grads.extend(layer.gradients_mut().into_iter().map(|(name, g)| (format!("{}.{}", prefix, name), g)));
+        }
+        grads
+    }
+
+    fn pass_state(&self) -> Option<PassState> {
+        self.pass.clone().map(|pass| pass as PassState)
+    }
+
+    fn set_pass_state(&mut self, state: Option<PassState>) {
+        self.pass = state.and_then(|state| state.downcast::<EncoderPass<T>>().ok());
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.attention.set_training(training);
+        self.dropout1.set_training(training);
+        self.dropout2.set_training(training);
+    }
+}
@@ -1,5 +1,9 @@
+pub mod callbacks;
 pub mod layer;
 pub mod tensor;
 pub mod types;
 pub mod loss;
-pub mod model;
\ No newline at end of file
+pub mod metrics;
+pub mod model;
+pub mod optim;
+pub mod rng;
\ No newline at end of file
@@ -0,0 +1,57 @@
+use num_traits::Float;
+
+use crate::metrics::Metric;
+use crate::tensor::Tensor;
+
+/// Fraction of outputs on the same side of `threshold` as their target.
+pub struct BinaryAccuracy<T> {
+    threshold: T,
+}
+
+impl<T> BinaryAccuracy<T>
+where
+    T: Float,
+{
+    /// Uses a threshold of 0.5
+    pub fn new() -> Self {
+        BinaryAccuracy {
+            threshold: T::from(0.5).unwrap(),
+        }
+    }
+
+    pub fn with_threshold(threshold: T) -> Self {
+        BinaryAccuracy { threshold }
+    }
+}
+
+impl<T> Default for BinaryAccuracy<T>
+where
+    T: Float,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Metric<T> for BinaryAccuracy<T>
+where
+    T: Float + Default,
+{
+    fn name(&self) -> &str {
+        "accuracy"
+    }
+
+    fn compute(&self, pred: &Tensor<T>, target: &Tensor<T>) -> T {
+        if pred.get_shape() != target.get_shape() {
+            panic!("Predicted and target tensors must have the same shape");
+        }
+
+        let hits = pred
+            .get_data()
+            .iter()
+            .zip(target.get_data())
+            .filter(|&(&p, &t)| (p > self.threshold) == (t > self.threshold))
+            .count();
+        T::from(hits).unwrap() / T::from(pred.get_size()).unwrap()
+    }
+}
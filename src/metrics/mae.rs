@@ -0,0 +1,25 @@
+use num_traits::Float;
+
+use crate::metrics::Metric;
+use crate::tensor::Tensor;
+
+/// Mean of `|pred - target|`.
+pub struct MeanAbsoluteError;
+
+impl<T> Metric<T> for MeanAbsoluteError
+where
+    T: Float + Default,
+{
+    fn name(&self) -> &str {
+        "mae"
+    }
+
+    fn compute(&self, pred: &Tensor<T>, target: &Tensor<T>) -> T {
+        if pred.get_shape() != target.get_shape() {
+            panic!("Predicted and target tensors must have the same shape");
+        }
+
+        let error = pred.sub(target).map(|x| x.abs()).sum_all().to_scalar();
+        error / T::from(pred.get_size()).unwrap()
+    }
+}
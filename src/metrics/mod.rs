@@ -0,0 +1,11 @@
+pub mod accuracy;
+pub mod mae;
+
+use crate::tensor::Tensor;
+
+/// Score reported during training and by `Sequential::evaluate`.
+/// It is computed per sample and averaged over the dataset.
+pub trait Metric<T> {
+    fn name(&self) -> &str;
+    fn compute(&self, pred: &Tensor<T>, target: &Tensor<T>) -> T;
+}
@@ -0,0 +1,111 @@
+use num_traits::Float;
+
+use crate::tensor::Tensor;
+
+/// Quantity watched by `EarlyStopping`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Monitor {
+    /// Training loss of the epoch
+    Loss,
+    /// Loss on the validation data; without validation data early stopping is
+    /// skipped and `TrainingHistory::warnings` says so
+    ValLoss,
+}
+
+/// Stops `Sequential::train` once the monitored loss has not improved by at
+/// least `min_delta` for `patience` epochs, and optionally restores the
+/// parameters and buffers of the best epoch when training ends.
+pub struct EarlyStopping<T> {
+    monitor: Monitor,
+    patience: usize,
+    min_delta: T,
+    restore_best_weights: bool,
+    best: Option<T>,
+    best_epoch: Option<usize>,
+    best_state: Option<Vec<Tensor<T>>>,
+    wait: usize,
+    stopped_epoch: Option<usize>,
+}
+
+impl<T> EarlyStopping<T>
+where
+    T: Float,
+{
+    pub fn new(monitor: Monitor, patience: usize, min_delta: T) -> Self {
+        EarlyStopping {
+            monitor,
+            patience,
+            min_delta,
+            restore_best_weights: true,
+            best: None,
+            best_epoch: None,
+            best_state: None,
+            wait: 0,
+            stopped_epoch: None,
+        }
+    }
+
+    /// Whether the best parameters and buffers are loaded back when training ends (default `true`).
+    pub fn with_restore_best_weights(mut self, restore: bool) -> Self {
+        self.restore_best_weights = restore;
+        self
+    }
+
+    pub fn monitor(&self) -> Monitor {
+        self.monitor
+    }
+
+    /// Best monitored value seen during the last `train` call.
+    pub fn best(&self) -> Option<T> {
+        self.best
+    }
+
+    /// 1-based epoch with the best monitored value.
+    pub fn best_epoch(&self) -> Option<usize> {
+        self.best_epoch
+    }
+
+    /// 1-based epoch at which training was stopped, `None` if it ran to the end.
+    pub fn stopped_epoch(&self) -> Option<usize> {
+        self.stopped_epoch
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.best = None;
+        self.best_epoch = None;
+        self.best_state = None;
+        self.wait = 0;
+        self.stopped_epoch = None;
+    }
+
+    /// Records the monitored value of `epoch`; `snapshot` is only called when it improved.
+    /// Returns `true` when training should stop.
+    pub(crate) fn update(&mut self, epoch: usize, value: T, snapshot: impl FnOnce() -> Vec<Tensor<T>>) -> bool {
+        let improved = match self.best {
+            Some(best) => value < best - self.min_delta,
+            None => !value.is_nan(),
+        };
+
+        if improved {
+            self.best = Some(value);
+            self.best_epoch = Some(epoch);
+            self.wait = 0;
+            if self.restore_best_weights {
+                self.best_state = Some(snapshot());
+            }
+            return false;
+        }
+
+        self.wait += 1;
+        if self.wait >= self.patience {
+            self.stopped_epoch = Some(epoch);
+            return true;
+        }
+        false
+    }
+
+    /// Parameters followed by buffers to restore at the end of training, if any.
+    pub(crate) fn take_best_state(&mut self) -> Option<Vec<Tensor<T>>> {
+        self.best_state.take()
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt::Display;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use num_traits::Float;
+
+use crate::model::early_stopping::Monitor;
+
+/// Per-epoch record of a `Sequential::train` run.
+#[derive(Clone, Debug, Default)]
+pub struct TrainingHistory<T> {
+    loss: Vec<T>,
+    val_loss: Vec<Option<T>>,
+    metrics: Vec<(String, Vec<T>)>,
+    learning_rates: Vec<T>,
+    grad_norms: Vec<T>,
+    epoch_times: Vec<Duration>,
+    warnings: Vec<String>,
+}
+
+impl<T> TrainingHistory<T>
+where
+    T: Float,
+{
+    pub fn new() -> Self {
+        TrainingHistory {
+            loss: Vec::new(),
+            val_loss: Vec::new(),
+            metrics: Vec::new(),
+            learning_rates: Vec::new(),
+            grad_norms: Vec::new(),
+            epoch_times: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Records a problem with the training configuration that did not stop the run.
+    pub fn push_warning(&mut self, warning: impl Into<String>) {
+        self.warnings.push(warning.into());
+    }
+
+    /// Appends one epoch. `metrics` must name the same metrics, in the same order, every epoch.
+    pub fn push_epoch(
+        &mut self,
+        loss: T,
+        val_loss: Option<T>,
+        metrics: &[(String, T)],
+        learning_rate: T,
+        grad_norm: T,
+        time: Duration,
+    ) {
+        if self.metrics.is_empty() {
+            self.metrics = metrics.iter().map(|(name, _)| (name.clone(), Vec::new())).collect();
+        }
+        if self.metrics.len() != metrics.len() {
+            panic!("Error: Every epoch must report the same metrics");
+        }
+        for ((_, values), (_, value)) in self.metrics.iter_mut().zip(metrics) {
+            values.push(*value);
+        }
+
+        self.loss.push(loss);
+        self.val_loss.push(val_loss);
+        self.learning_rates.push(learning_rate);
+        self.grad_norms.push(grad_norm);
+        self.epoch_times.push(time);
+    }
+
+    pub fn epochs(&self) -> usize {
+        self.loss.len()
+    }
+
+    /// Mean training loss of every epoch
+    pub fn loss(&self) -> &[T] {
+        &self.loss
+    }
+
+    /// Validation loss of every epoch (`None` for epochs without validation data)
+    pub fn val_loss(&self) -> &[Option<T>] {
+        &self.val_loss
+    }
+
+    /// Values of a metric by name, e.g. `"mae"` or `"val_mae"`
+    pub fn metric(&self, name: &str) -> Option<&[T]> {
+        self.metrics
+            .iter()
+            .find(|(metric, _)| metric == name)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    pub fn metric_names(&self) -> Vec<&str> {
+        self.metrics.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn learning_rates(&self) -> &[T] {
+        &self.learning_rates
+    }
+
+    /// Mean pre-clip global gradient norm of every epoch
+    pub fn grad_norms(&self) -> &[T] {
+        &self.grad_norms
+    }
+
+    /// Wall-clock time spent on every epoch
+    pub fn epoch_times(&self) -> &[Duration] {
+        &self.epoch_times
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.epoch_times.iter().sum()
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// 1-based epoch with the lowest monitored loss; NaN epochs are ignored.
+    pub fn best_epoch(&self, monitor: Monitor) -> Option<usize> {
+        let values: Vec<Option<T>> = match monitor {
+            Monitor::Loss => self.loss.iter().map(|&x| Some(x)).collect(),
+            Monitor::ValLoss => self.val_loss.clone(),
+        };
+
+        let mut best: Option<(usize, T)> = None;
+        for (epoch, value) in values.into_iter().enumerate() {
+            if let Some(value) = value.filter(|v| !v.is_nan())
+                && best.is_none_or(|(_, b)| value < b)
+            {
+                best = Some((epoch + 1, value));
+            }
+        }
+        best.map(|(epoch, _)| epoch)
+    }
+
+}
+
+impl<T> TrainingHistory<T>
+where
+    T: Float + Display,
+{
+    /// One row per epoch: `epoch,loss,val_loss,<metrics...>,learning_rate,grad_norm,time_secs`.
+    /// Missing and non-finite values are left empty; metric names are quoted per RFC 4180 when needed.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["epoch".to_string(), "loss".to_string(), "val_loss".to_string()];
+        header.extend(self.metrics.iter().map(|(name, _)| csv_field(name)));
+        header.extend(["learning_rate", "grad_norm", "time_secs"].map(String::from));
+        let mut out = header.join(",");
+        out.push('\n');
+
+        for i in 0..self.epochs() {
+            let mut row = vec![(i + 1).to_string(), csv_number(Some(self.loss[i])), csv_number(self.val_loss[i])];
+            row.extend(self.metrics.iter().map(|(_, values)| csv_number(Some(values[i]))));
+            row.push(csv_number(Some(self.learning_rates[i])));
+            row.push(csv_number(Some(self.grad_norms[i])));
+            row.push(self.epoch_times[i].as_secs_f64().to_string());
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Object of per-epoch arrays, one key per recorded series. Missing and non-finite values become `null`.
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![
+            json_field("loss", self.loss.iter().map(|&x| Some(x))),
+            json_field("val_loss", self.val_loss.iter().copied()),
+        ];
+        for (name, values) in &self.metrics {
+            fields.push(json_field(name, values.iter().map(|&x| Some(x))));
+        }
+        fields.push(json_field("learning_rate", self.learning_rates.iter().map(|&x| Some(x))));
+        fields.push(json_field("grad_norm", self.grad_norms.iter().map(|&x| Some(x))));
+        fields.push(format!(
+            "\"time_secs\":[{}]",
+            self.epoch_times
+                .iter()
+                .map(|t| t.as_secs_f64().to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        ));
+        format!("{{{}}}", fields.join(","))
+    }
+
+    pub fn save_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_csv())
+    }
+
+    pub fn save_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+}
+
+/// Text of a finite value; missing and non-finite values are `None` in both formats.
+fn number<T: Float + Display>(value: Option<T>) -> Option<String> {
+    value.filter(|v| v.is_finite()).map(|v| v.to_string())
+}
+
+fn csv_number<T: Float + Display>(value: Option<T>) -> String {
+    number(value).unwrap_or_default()
+}
+
+/// Wraps `field` in quotes, doubling inner quotes, when it holds a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn json_field<T: Float + Display>(name: &str, values: impl Iterator<Item = Option<T>>) -> String {
+    let values: Vec<String> = values
+        .map(|value| number(value).unwrap_or_else(|| "null".to_string()))
+        .collect();
+    let name = name
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("\"{}\":[{}]", name, values.join(","))
+}
@@ -1 +1,3 @@
+pub mod early_stopping;
+pub mod history;
 pub mod sequential;
\ No newline at end of file
@@ -1,9 +1,57 @@
-use crate::layer::trainable::{TrainableLayer, TrainableModel};
+use std::any::Any;
+use std::time::Instant;
+
+use num_traits::Float;
+use rand::seq::SliceRandom;
+
+use crate::callbacks::{BatchLogs, Callback, EpochLogs};
+use crate::layer::activation::ActivationFn;
+use crate::layer::trainable::{NamedTensors, NamedTensorsMut, PassState, TrainableLayer, TrainableModel};
+use crate::metrics::Metric;
+use crate::model::early_stopping::{EarlyStopping, Monitor};
+use crate::model::history::TrainingHistory;
+use crate::optim::clip::{global_norm, GradientClipping};
+use crate::optim::sgd::Sgd;
+use crate::optim::Optimizer;
 use crate::tensor::Tensor;
 use crate::loss::Loss;
 
+/// Intermediate values of a training forward pass, consumed by `Sequential::backward`.
+/// Each cache carries the per-pass state of every layer, so several caches can be
+/// in flight and each one can be backpropagated more than once.
+pub struct ForwardCache<T> {
+    inputs: Vec<Tensor<T>>, // input of every layer
+    states: Vec<Option<PassState>>,
+    output: Tensor<T>,
+}
+
+impl<T> ForwardCache<T> {
+    pub fn output(&self) -> &Tensor<T> {
+        &self.output
+    }
+}
+
+/// Inputs and their targets
+type Dataset<T> = (Vec<Tensor<T>>, Vec<Tensor<T>>);
+
+/// Result of `Sequential::evaluate`: mean loss and mean value of every metric.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evaluation<T> {
+    pub loss: T,
+    pub metrics: Vec<(String, T)>,
+}
+
 pub struct Sequential<T> {
     layers: Vec<Box<dyn TrainableLayer<T>>>,
+    clipping: Option<GradientClipping<T>>,
+    accumulation_steps: usize,
+    callbacks: Vec<Box<dyn Callback<T>>>,
+    early_stopping: Option<EarlyStopping<T>>,
+    metrics: Vec<Box<dyn Metric<T>>>,
+    validation_data: Option<Dataset<T>>,
+    validation_split: Option<f32>,
+    shuffle: bool,
+    training: bool,
 }
 
 impl<T> Sequential<T>
@@ -11,14 +59,231 @@ where
     T:'static + Copy + Default + std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T>,
 {
     pub fn new() -> Self {
-        Self { layers: Vec::new() }
+        Self {
+            layers: Vec::new(),
+            clipping: None,
+            accumulation_steps: 1,
+            callbacks: Vec::new(),
+            early_stopping: None,
+            metrics: Vec::new(),
+            validation_data: None,
+            validation_split: None,
+            shuffle: false,
+            training: false,
+        }
     }
 
-    pub fn add(&mut self, layer: impl TrainableLayer<T> + 'static) {
+    pub fn add(&mut self, mut layer: impl TrainableLayer<T> + 'static) {
+        layer.set_training(self.training);
         self.layers.push(Box::new(layer));
     }
 
-    pub fn forward(&self, input: &Tensor<T>, activations: &[Option<fn(T) -> T>]) -> Tensor<T> {
+    /// Layer `index` as its concrete type, e.g. `model.layer::<LSTM<f32>>(0)`;
+    /// `None` if the index is out of range or the layer has another type.
+    pub fn layer<L: TrainableLayer<T>>(&self, index: usize) -> Option<&L> {
+        let layer: &dyn Any = self.layers.get(index)?.as_ref();
+        layer.downcast_ref()
+    }
+
+    /// Mutable `layer`, to set per-layer state such as an initial state or a mask
+    /// between batches of a custom training loop.
+    pub fn layer_mut<L: TrainableLayer<T>>(&mut self, index: usize) -> Option<&mut L> {
+        let layer: &mut dyn Any = self.layers.get_mut(index)?.as_mut();
+        layer.downcast_mut()
+    }
+
+    /// Switches every layer between training (`true`) and inference (`false`) mode.
+    /// Models start in inference mode; `train` enables training mode while it runs
+    /// and restores the previous mode afterwards.
+    pub fn set_training(&mut self, training: bool) {
+        self.training = training;
+        for layer in self.layers.iter_mut() {
+            layer.set_training(training);
+        }
+    }
+
+    pub fn is_training(&self) -> bool {
+        self.training
+    }
+
+    /// Clips the gradients of every layer before each update (disabled with `None`).
+    pub fn set_gradient_clipping(&mut self, clipping: Option<GradientClipping<T>>) {
+        self.clipping = clipping;
+    }
+
+    /// Registers a callback for `train`. With none registered, `train` reports
+    /// nothing; add `Logger` or `ProgressBar` to follow the epochs.
+    pub fn add_callback(&mut self, callback: impl Callback<T> + 'static) {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    /// Metric reported by `train` on the training data and, prefixed with
+    /// `val_`, on the validation data.
+    pub fn add_metric(&mut self, metric: impl Metric<T> + 'static) {
+        self.metrics.push(Box::new(metric));
+    }
+
+    /// Held-out data scored after every epoch of `train`. Takes precedence over `set_validation_split`.
+    pub fn set_validation_data(&mut self, inputs: Vec<Tensor<T>>, targets: Vec<Tensor<T>>) {
+        if inputs.len() != targets.len() {
+            panic!("Error: Validation inputs and targets must have the same length");
+        }
+        self.validation_data = Some((inputs, targets));
+    }
+
+    pub fn clear_validation_data(&mut self) {
+        self.validation_data = None;
+    }
+
+    /// Fraction of the training samples (taken from the end, before any
+    /// training) kept aside as validation data. `None` disables it.
+    pub fn set_validation_split(&mut self, fraction: Option<f32>) {
+        if let Some(fraction) = fraction
+            && !(0.0..1.0).contains(&fraction)
+        {
+            panic!("Error: Validation split must be in [0, 1)");
+        }
+        self.validation_split = fraction;
+    }
+
+    /// Visit the training samples in a new random order every epoch, drawn
+    /// from the crate generator (see `rng::set_seed`).
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+    }
+
+    /// Stops `train` early when the monitored loss stops improving (disabled with `None`).
+    pub fn set_early_stopping(&mut self, early_stopping: Option<EarlyStopping<T>>) {
+        self.early_stopping = early_stopping;
+    }
+
+    /// Early stopping state of the last `train` call (best and stopped epoch).
+    pub fn early_stopping(&self) -> Option<&EarlyStopping<T>> {
+        self.early_stopping.as_ref()
+    }
+
+    /// Number of samples whose gradients are averaged before each update.
+    pub fn set_accumulation_steps(&mut self, steps: usize) {
+        if steps == 0 {
+            panic!("Error: Accumulation steps must be at least 1");
+        }
+        self.accumulation_steps = steps;
+    }
+
+    /// Sum of the regularization penalties of every layer.
+    pub fn regularization_loss(&self) -> T {
+        self.layers
+            .iter()
+            .fold(T::default(), |acc, layer| acc + layer.regularization_loss())
+    }
+
+    /// Parameters of every layer, named `<layer index>.<parameter name>`.
+    pub fn parameters(&self) -> NamedTensors<'_, T> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .parameters()
+                    .into_iter()
+                    .map(move |(name, param)| (format!("{}.{}", i, name), param))
+            })
+            .collect()
+    }
+
+    pub fn parameters_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .parameters_mut()
+                    .into_iter()
+                    .map(move |(name, param)| (format!("{}.{}", i, name), param))
+            })
+            .collect()
+    }
+
+    /// Gradients of every layer, in the same order as `parameters`.
+    pub fn gradients(&self) -> NamedTensors<'_, T> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .gradients()
+                    .into_iter()
+                    .map(move |(name, grad)| (format!("{}.{}", i, name), grad))
+            })
+            .collect()
+    }
+
+    pub fn gradients_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .gradients_mut()
+                    .into_iter()
+                    .map(move |(name, grad)| (format!("{}.{}", i, name), grad))
+            })
+            .collect()
+    }
+
+    /// Rows of every gradient that may be non-zero, in the same order as `gradients`
+    /// (`None` for dense gradients).
+    pub fn gradient_rows(&self) -> Vec<Option<Vec<usize>>> {
+        self.layers.iter().flat_map(|layer| layer.gradient_rows()).collect()
+    }
+
+    /// Buffers of every layer, named like `parameters`.
+    pub fn buffers(&self) -> NamedTensors<'_, T> {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .buffers()
+                    .into_iter()
+                    .map(move |(name, buffer)| (format!("{}.{}", i, name), buffer))
+            })
+            .collect()
+    }
+
+    pub fn buffers_mut(&mut self) -> NamedTensorsMut<'_, T> {
+        self.layers
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, layer)| {
+                layer
+                    .buffers_mut()
+                    .into_iter()
+                    .map(move |(name, buffer)| (format!("{}.{}", i, name), buffer))
+            })
+            .collect()
+    }
+
+    pub fn num_parameters(&self) -> usize {
+        self.parameters().iter().map(|(_, param)| param.get_size()).sum()
+    }
+
+    /// One line per layer with the shape of each of its parameters.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let params = layer.parameters();
+            let count: usize = params.iter().map(|(_, param)| param.get_size()).sum();
+            out.push_str(&format!("{}: {} ({} params)\n", i, layer.name(), count));
+            for (name, param) in params {
+                out.push_str(&format!("    {} {:?}\n", name, param.get_shape()));
+            }
+        }
+        out.push_str(&format!("Total params: {}\n", self.num_parameters()));
+        out
+    }
+
+    pub fn forward(&self, input: &Tensor<T>, activations: &[Option<ActivationFn<T>>]) -> Tensor<T> {
         let mut out = input.clone();
         for (i, layer) in self.layers.iter().enumerate() {
             out = layer.forward(&out, activations[i]).unwrap();
@@ -26,14 +291,128 @@ where
         out
     }
 
-    pub fn predict_all(&self, inputs: &[Tensor<T>], activations: &[Option<fn(T) -> T>]) -> Vec<Tensor<T>> {
+    /// Forward pass that keeps the input and pass state of every layer for `backward`.
+    pub fn forward_train(&mut self, input: &Tensor<T>, activations: &[Option<ActivationFn<T>>]) -> ForwardCache<T> {
+        let mut inputs = Vec::with_capacity(self.layers.len());
+        let mut states = Vec::with_capacity(self.layers.len());
+        let mut out = input.clone();
+        for (i, layer) in self.layers.iter_mut().enumerate() {
+            let next = layer.forward_train(&out, activations[i]).unwrap();
+            inputs.push(out);
+            states.push(layer.pass_state());
+            out = next;
+        }
+        ForwardCache { inputs, states, output: out }
+    }
+
+    /// Backpropagates `grad_output` through every layer, adding the parameter
+    /// gradients to the ones already stored on the layers.
+    /// Returns the gradient with respect to the model input.
+    pub fn backward(&mut self, cache: &ForwardCache<T>, grad_output: &Tensor<T>) -> Tensor<T> {
+        let mut grad = grad_output.clone();
+        for i in (0..self.layers.len()).rev() {
+            self.layers[i].set_pass_state(cache.states[i].clone());
+            grad = self.layers[i].backward(&cache.inputs[i], &grad);
+        }
+        grad
+    }
+
+    pub fn zero_grad(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.zero_grad();
+        }
+    }
+
+    /// Multiplies every stored gradient by `factor` (e.g. `1 / n` to average accumulated steps).
+    pub fn scale_gradients(&mut self, factor: T) {
+        for (_, grad) in self.gradients_mut() {
+            for value in grad.get_data_mut() {
+                *value = *value * factor;
+            }
+        }
+    }
+
+    /// Applies every layer's constraints; optimizers call it after each update.
+    pub fn apply_constraints(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.apply_constraints();
+        }
+    }
+
+    pub fn predict_all(&self, inputs: &[Tensor<T>], activations: &[Option<ActivationFn<T>>]) -> Vec<Tensor<T>> {
         inputs.iter().map(|x| self.forward(x, activations)).collect()
     }
 }
 
+impl<T> Default for Sequential<T>
+where
+    T:'static + Copy + Default + std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Sequential<T>
+where
+    T: 'static + Float + Default,
+{
+    /// Inference-only pass over `inputs`, returning the mean loss and the mean of every metric.
+    pub fn evaluate<L>(
+        &self,
+        inputs: &[Tensor<T>],
+        targets: &[Tensor<T>],
+        loss_fn: &L,
+        metrics: &[Box<dyn Metric<T>>],
+        activations: &[Option<ActivationFn<T>>],
+    ) -> Evaluation<T>
+    where
+        L: Loss<T>,
+    {
+        let mut total_loss = T::zero();
+        let mut totals = vec![T::zero(); metrics.len()];
+        let mut count = 0;
+
+        for (input, target) in inputs.iter().zip(targets.iter()) {
+            let pred = self.forward(input, activations);
+            total_loss = total_loss + loss_fn.forward(&pred, target).get_data()[0];
+            for (total, metric) in totals.iter_mut().zip(metrics) {
+                *total = *total + metric.compute(&pred, target);
+            }
+            count += 1;
+        }
+
+        let n = T::from(count.max(1)).unwrap();
+        Evaluation {
+            loss: total_loss / n,
+            metrics: metrics
+                .iter()
+                .zip(totals)
+                .map(|(metric, total)| (metric.name().to_string(), total / n))
+                .collect(),
+        }
+    }
+
+    /// Applies the configured gradient clipping (if any) to the stored gradients.
+    /// Returns the global gradient norm before clipping.
+    pub fn clip_gradients(&mut self) -> T {
+        let mut grads: Vec<Tensor<T>> = self.gradients().into_iter().map(|(_, grad)| grad.clone()).collect();
+
+        let norm = match &self.clipping {
+            Some(clipping) => clipping.apply(&mut grads),
+            None => return global_norm(&grads),
+        };
+
+        for ((_, grad), clipped) in self.gradients_mut().into_iter().zip(grads) {
+            *grad = clipped;
+        }
+        norm
+    }
+}
+
 impl<T, L> TrainableModel<T, L> for Sequential<T>
 where
-    T: 'static + Copy + Default + std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T> + std::fmt::Debug,
+    T: 'static + Float + Default + std::fmt::Debug,
     L: Loss<T>,
 {
     fn train(
@@ -43,37 +422,176 @@ where
         loss_fn: &L,
         epochs: usize,
         learning_rate: T,
-        activations: &[Option<fn(T) -> T>],
-    ) -> Vec<T> {
-        let mut history = Vec::with_capacity(epochs);
+        activations: &[Option<ActivationFn<T>>],
+    ) -> TrainingHistory<T> {
+        let mut history = TrainingHistory::new();
+        let mut optimizer = Sgd::new(learning_rate);
+        self.zero_grad();
+        let was_training = self.training;
+        self.set_training(true);
+
+        let mut callbacks = std::mem::take(&mut self.callbacks);
+        // Split off the validation data before training
+        let samples = inputs.len().min(targets.len());
+        let validation_data = self.validation_data.take();
+        let (inputs, targets, validation) = match (&validation_data, self.validation_split) {
+            (Some((val_inputs, val_targets)), _) => (
+                &inputs[..samples],
+                &targets[..samples],
+                Some((val_inputs.as_slice(), val_targets.as_slice())),
+            ),
+            (None, Some(fraction)) => {
+                let split = samples - (samples as f32 * fraction).round() as usize;
+                (
+                    &inputs[..split],
+                    &targets[..split],
+                    Some((&inputs[split..samples], &targets[split..samples])),
+                )
+            }
+            (None, None) => (&inputs[..samples], &targets[..samples], None),
+        };
+        let metrics = std::mem::take(&mut self.metrics);
+
+        let batches = inputs.len();
+        let mut early_stopping = self.early_stopping.take();
+        if let Some(early_stopping) = early_stopping.as_mut() {
+            early_stopping.reset();
+            if early_stopping.monitor() == Monitor::ValLoss && validation.is_none() {
+                history.push_warning("EarlyStopping monitors ValLoss but there is no validation data; it is skipped");
+            }
+        }
+        for callback in callbacks.iter_mut() {
+            callback.on_train_begin(epochs, batches);
+        }
 
         for epoch in 0..epochs {
+            let started = Instant::now();
             let mut total_loss = T::default();
+            let mut total_norm = T::zero();
+            let mut steps = 0;
+            let mut pending = 0;
+            let mut metric_totals = vec![T::zero(); metrics.len()];
+
+            let mut order: Vec<usize> = (0..batches).collect();
+            if self.shuffle {
+                crate::rng::with_rng(|rng| order.shuffle(rng));
+            }
 
-            for (input, target) in inputs.iter().zip(targets.iter()) {
-                // FORWARD
-                let mut activations_cache = vec![input.clone()];
-                let mut output = input.clone();
-                for (i, layer) in self.layers.iter().enumerate() {
-                    output = layer.forward(&output, activations[i]).unwrap();
-                    activations_cache.push(output.clone());
+            for (n, &sample) in order.iter().enumerate() {
+                let (input, target) = (&inputs[sample], &targets[sample]);
+                let cache = self.forward_train(input, activations);
+
+                let loss = loss_fn.forward(cache.output(), target).get_data()[0] + self.regularization_loss();
+                total_loss = total_loss + loss;
+                for (total, metric) in metric_totals.iter_mut().zip(&metrics) {
+                    *total = *total + metric.compute(cache.output(), target);
+                }
+
+                let grad = loss_fn.backward(cache.output(), target);
+                self.backward(&cache, &grad);
+                pending += 1;
+
+                // UPDATE every `accumulation_steps` samples and at the end of the epoch
+                let last = n + 1 == batches;
+                if pending == self.accumulation_steps || last {
+                    self.scale_gradients(T::one() / T::from(pending).unwrap());
+                    total_norm = total_norm + self.clip_gradients();
+                    optimizer.step(self);
+                    self.zero_grad();
+                    pending = 0;
+                    steps += 1;
+                }
+
+                let logs = BatchLogs {
+                    epoch: epoch + 1,
+                    batch: n + 1,
+                    loss,
+                };
+                for callback in callbacks.iter_mut() {
+                    callback.on_batch_end(&logs);
+                }
+            }
+
+            let grad_norm = if steps > 0 {
+                total_norm / T::from(steps).unwrap()
+            } else {
+                T::zero()
+            };
+            let n = T::from(batches.max(1)).unwrap();
+            let mean_loss = total_loss / n;
+            let mut epoch_metrics: Vec<(String, T)> = metrics
+                .iter()
+                .zip(metric_totals)
+                .map(|(metric, total)| (metric.name().to_string(), total / n))
+                .collect();
+
+            let val_loss = validation.map(|(val_inputs, val_targets)| {
+                self.set_training(false);
+                let evaluation = self.evaluate(val_inputs, val_targets, loss_fn, &metrics, activations);
+                self.set_training(true);
+                for (name, value) in evaluation.metrics {
+                    epoch_metrics.push((format!("val_{}", name), value));
                 }
+                evaluation.loss
+            });
+            history.push_epoch(
+                mean_loss,
+                val_loss,
+                &epoch_metrics,
+                optimizer.learning_rate(),
+                grad_norm,
+                started.elapsed(),
+            );
+            epoch_metrics.push(("grad_norm".to_string(), grad_norm));
 
-                let loss = loss_fn.forward(&output, target);
-                total_loss = total_loss + loss.get_data()[0];
+            let logs = EpochLogs {
+                epoch: epoch + 1,
+                loss: mean_loss,
+                val_loss,
+                metrics: epoch_metrics,
+            };
+            for callback in callbacks.iter_mut() {
+                callback.on_epoch_end(&logs);
+            }
 
-                // BACKWARD
-                let mut grad = loss_fn.backward(&output, target);
-                for i in (0..self.layers.len()).rev() {
-                    let (grad_input, grad_w, grad_b) = self.layers[i].backward(&activations_cache[i], &grad);
-                    self.layers[i].update_params(&grad_w, &grad_b, learning_rate);
-                    grad = grad_input;
+            if let Some(early_stopping) = early_stopping.as_mut() {
+                let value = match early_stopping.monitor() {
+                    Monitor::Loss => Some(logs.loss),
+                    Monitor::ValLoss => logs.val_loss,
+                };
+                let stop = value.is_some_and(|value| {
+                    early_stopping.update(epoch + 1, value, || {
+                        self.parameters()
+                            .into_iter()
+                            .chain(self.buffers())
+                            .map(|(_, tensor)| tensor.clone())
+                            .collect()
+                    })
+                });
+                if stop {
+                    break;
                 }
             }
+        }
+
+        if let Some(best_state) = early_stopping.as_mut().and_then(|e| e.take_best_state()) {
+            let mut best_state = best_state.into_iter();
+            for ((_, param), best) in self.parameters_mut().into_iter().zip(best_state.by_ref()) {
+                *param = best;
+            }
+            for ((_, buffer), best) in self.buffers_mut().into_iter().zip(best_state) {
+                *buffer = best;
+            }
+        }
+        self.set_training(was_training);
+        self.early_stopping = early_stopping;
+        self.metrics = metrics;
+        self.validation_data = validation_data;
 
-            println!("Epoch {} - Loss: {:?}", epoch + 1, total_loss);
-            history.push(total_loss);
+        for callback in callbacks.iter_mut() {
+            callback.on_train_end(&history);
         }
+        self.callbacks = callbacks;
 
         history
     }
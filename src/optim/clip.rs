@@ -0,0 +1,64 @@
+use num_traits::Float;
+
+use crate::tensor::Tensor;
+
+/// Gradient clipping strategy applied before the parameter update.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientClipping<T> {
+    /// Clamp every gradient component into `[-limit, limit]`.
+    Value(T),
+    /// Rescale all gradients together so their global L2 norm is at most `max_norm`.
+    GlobalNorm(T),
+}
+
+impl<T> GradientClipping<T>
+where
+    T: Float + Default,
+{
+    /// Clips `grads` in place and returns their global L2 norm before clipping.
+    pub fn apply(&self, grads: &mut [Tensor<T>]) -> T {
+        let norm = global_norm(grads);
+
+        match *self {
+            GradientClipping::Value(limit) => {
+                for grad in grads.iter_mut() {
+                    *grad = clip_by_value(grad, limit);
+                }
+            }
+            GradientClipping::GlobalNorm(max_norm) => {
+                if norm > max_norm {
+                    let factor = max_norm / norm;
+                    for grad in grads.iter_mut() {
+                        *grad = grad.scale(factor);
+                    }
+                }
+            }
+        }
+
+        norm
+    }
+}
+
+/// L2 norm of all the gradients taken as a single flat vector.
+pub fn global_norm<T>(grads: &[Tensor<T>]) -> T
+where
+    T: Float + Default,
+{
+    let mut sum_sq = T::zero();
+    for grad in grads {
+        for &x in grad.get_data() {
+            sum_sq = sum_sq + x * x;
+        }
+    }
+    sum_sq.sqrt()
+}
+
+pub fn clip_by_value<T>(grad: &Tensor<T>, limit: T) -> Tensor<T>
+where
+    T: Float + Default,
+{
+    if limit < T::zero() {
+        panic!("Error: Clipping limit must be non-negative");
+    }
+    grad.map(|x| x.max(-limit).min(limit))
+}
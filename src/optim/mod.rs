@@ -0,0 +1,13 @@
+pub mod clip;
+pub mod sgd;
+
+use crate::model::sequential::Sequential;
+
+/// Updates the parameters of a model from the gradients stored on its layers.
+///
+/// A custom training loop looks like:
+/// `model.zero_grad()`, `model.forward_train(..)`, `model.backward(..)`, `optimizer.step(&mut model)`.
+pub trait Optimizer<T> {
+    fn step(&mut self, model: &mut Sequential<T>);
+    fn learning_rate(&self) -> T;
+}
@@ -0,0 +1,78 @@
+use num_traits::Float;
+
+use crate::model::sequential::Sequential;
+use crate::optim::Optimizer;
+use crate::tensor::Tensor;
+
+/// Plain stochastic gradient descent: `param -= learning_rate * grad`.
+///
+/// Sparse gradients (see `TrainableLayer::gradient_rows`) only update their listed rows.
+pub struct Sgd<T> {
+    learning_rate: T,
+}
+
+impl<T> Sgd<T> {
+    pub fn new(learning_rate: T) -> Self {
+        Sgd { learning_rate }
+    }
+}
+
+/// Elements per row (first axis) of `tensor`
+fn row_width<T>(tensor: &Tensor<T>) -> usize
+where
+    T: Float + Default,
+{
+    tensor.get_size() / tensor.get_shape()[0]
+}
+
+impl<T> Optimizer<T> for Sgd<T>
+where
+    T: 'static + Float + Default,
+{
+    fn step(&mut self, model: &mut Sequential<T>) {
+        // Sparse gradients only copy out their touched rows
+        let grads: Vec<(Option<Vec<usize>>, Vec<T>)> = model
+            .gradients()
+            .into_iter()
+            .zip(model.gradient_rows())
+            .map(|((_, grad), rows)| {
+                let data = match &rows {
+                    Some(rows) => {
+                        let width = row_width(grad);
+                        rows.iter()
+                            .flat_map(|&row| &grad.get_data()[row * width..(row + 1) * width])
+                            .copied()
+                            .collect()
+                    }
+                    None => grad.get_data().clone(),
+                };
+                (rows, data)
+            })
+            .collect();
+
+        for ((_, param), (rows, grad)) in model.parameters_mut().into_iter().zip(grads) {
+            match rows {
+                Some(rows) => {
+                    let width = row_width(param);
+                    let data = param.get_data_mut();
+                    for (&row, grad) in rows.iter().zip(grad.chunks(width)) {
+                        for (value, &g) in data[row * width..(row + 1) * width].iter_mut().zip(grad) {
+                            *value = *value - self.learning_rate * g;
+                        }
+                    }
+                }
+                None => {
+                    for (value, &g) in param.get_data_mut().iter_mut().zip(&grad) {
+                        *value = *value - self.learning_rate * g;
+                    }
+                }
+            }
+        }
+
+        model.apply_constraints();
+    }
+
+    fn learning_rate(&self) -> T {
+        self.learning_rate
+    }
+}
@@ -0,0 +1,45 @@
+//! Crate-wide random number generator.
+//!
+//! Every random draw in the crate (weight init, sample shuffling, dropout
+//! masks) goes through `with_rng`, so calling `set_seed` before building and
+//! training a model reproduces the run exactly. Without a seed the generator is seeded
+//! from the OS. Each thread owns its generator; threads started after
+//! `set_seed` are seeded with the same value.
+
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+static GLOBAL_SEED: Mutex<Option<u64>> = Mutex::new(None);
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(new_rng());
+}
+
+fn new_rng() -> StdRng {
+    match *GLOBAL_SEED.lock().unwrap() {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    }
+}
+
+/// Seeds the generator of the current thread and of threads created afterwards.
+pub fn set_seed(seed: u64) {
+    *GLOBAL_SEED.lock().unwrap() = Some(seed);
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Seed set with `set_seed`, if any.
+pub fn seed() -> Option<u64> {
+    *GLOBAL_SEED.lock().unwrap()
+}
+
+/// Runs `f` with the crate generator of the current thread.
+pub fn with_rng<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut StdRng) -> R,
+{
+    RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
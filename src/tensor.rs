@@ -27,6 +27,11 @@ where
         }
     }
 
+    pub fn zeros(accuracy: Accuracy, shape: Vec<usize>) -> Self {
+        let size = shape.iter().product();
+        Tensor::new(accuracy, vec![T::default(); size], shape)
+    }
+
     pub fn add(&self, other: &Tensor<T>) -> Tensor<T> {
         if self.accuracy != other.accuracy {
             panic!("Error: Cannot add Tensors with different accuracies");
@@ -291,6 +296,20 @@ where
         Tensor { data: scaled_data, shape: self.shape.clone(), size: self.size, accuracy: self.accuracy }
     }
 
+    pub fn reshape(&self, shape: Vec<usize>) -> Tensor<T> {
+        let size: usize = shape.iter().product();
+        if size != self.size {
+            panic!("Error: Cannot reshape Tensor to a shape with a different number of elements");
+        }
+
+        Tensor {
+            data: self.data.clone(),
+            shape,
+            size,
+            accuracy: self.accuracy,
+        }
+    }
+
     pub fn get_shape(&self) -> &Vec<usize> {
         &self.shape
     }
@@ -299,6 +318,11 @@ where
         &self.data
     }
 
+    /// Mutable view of the data; the shape cannot change through it
+    pub fn get_data_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
     pub fn get_size(&self) -> usize {
         self.size
     }
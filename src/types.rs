@@ -9,37 +9,41 @@ pub enum Accuracy {
     F32,
 }
 
-pub trait Randomizable {
-    fn random_weight() -> Self;
+pub trait Randomizable: Sized {
+    /// Converts a real-valued weight into this type
+    fn from_weight(val: f32) -> Self;
+
+    /// Draws a weight uniformly from [-0.1, 0.1] using `rng`
+    fn random_weight_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::from_weight(rng.random_range(-0.1f32..=0.1))
+    }
+
+    /// Draws a weight from the crate generator (see `rng::set_seed`)
+    fn random_weight() -> Self {
+        crate::rng::with_rng(|rng| Self::random_weight_with(rng))
+    }
 }
 
 impl Randomizable for u8 {
-    fn random_weight() -> Self {
-        let mut rng = rand::rng();
-        let val = rng.random_range(-0.1f32..=0.1);
+    fn from_weight(val: f32) -> Self {
         ((val * 127.0) + 128.0).clamp(0.0, 255.0) as u8
     }
 }
 
 impl Randomizable for i8 {
-    fn random_weight() -> Self {
-        let mut rng = rand::rng();
-        let val = rng.random_range(-0.1f32..=0.1);
+    fn from_weight(val: f32) -> Self {
         (val * 127.0).clamp(-128.0, 127.0) as i8
     }
 }
 
 impl Randomizable for f16 {
-    fn random_weight() -> Self {
-        let mut rng = rand::rng();
-        let val = rng.random_range(-0.1f32..=0.1);
+    fn from_weight(val: f32) -> Self {
         f16::from_f32(val)
     }
 }
 
 impl Randomizable for f32 {
-    fn random_weight() -> Self {
-        let mut rng = rand::rng();
-        rng.random_range(-0.1..=0.1)
+    fn from_weight(val: f32) -> Self {
+        val
     }
 }
@@ -0,0 +1,130 @@
+mod common;
+
+use littleflow::layer::attention::MultiHeadAttention;
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::model::sequential::Sequential;
+use littleflow::rng;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+fn padding_mask() -> Tensor<f32> {
+    // Last position of the second sample is padding
+    Tensor::new(Accuracy::F32, vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0], vec![2, 4])
+}
+
+#[test]
+fn masks_hide_future_and_padded_keys() {
+    let input = common::pattern(vec![2, 4, 6], 0.0);
+    let mut layer = MultiHeadAttention::<f32>::new(6, 2, Accuracy::F32).with_causal(true);
+    layer.set_padding_mask(Some(padding_mask()));
+
+    let output = layer.forward(&input, None).unwrap();
+    assert_eq!(output.get_shape(), input.get_shape());
+
+    let weights = layer.attention_weights(&input).unwrap();
+    assert_eq!(weights.get_shape(), &vec![2, 2, 4, 4]);
+    for (n, row) in weights.get_data().chunks(4).enumerate() {
+        let (sample, query) = (n / 8, n % 4);
+        for (key, &w) in row.iter().enumerate() {
+            if key > query || (sample == 1 && key == 3) {
+                assert_eq!(w, 0.0);
+            }
+        }
+        assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+    }
+
+    // Changing a later position does not change earlier outputs under the causal mask
+    let mut changed = input.get_data().clone();
+    changed[18] += 1.0;
+    let changed = layer
+        .forward(&Tensor::new(Accuracy::F32, changed, vec![2, 4, 6]), None)
+        .unwrap();
+    assert_eq!(&changed.get_data()[..18], &output.get_data()[..18]);
+}
+
+#[test]
+fn fully_padded_sample_attends_to_nothing() {
+    // Every position of the second sample is padding
+    let mask = Tensor::new(Accuracy::F32, vec![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0], vec![2, 4]);
+    let input = common::pattern(vec![2, 4, 6], 0.0);
+    let mut layer = MultiHeadAttention::<f32>::new(6, 2, Accuracy::F32);
+    layer.set_padding_mask(Some(mask));
+
+    let weights = layer.attention_weights(&input).unwrap();
+    assert!(weights.get_data()[32..].iter().all(|&w| w == 0.0));
+
+    // Zero context plus the zero output bias
+    let output = layer.forward_train(&input, None).unwrap();
+    assert!(output.get_data()[24..].iter().all(|&x| x == 0.0));
+    assert!(output.get_data()[..24].iter().all(|x| x.is_finite()));
+
+    let grad = layer.backward(&input, &common::pattern(vec![2, 4, 6], 3.0));
+    assert!(grad.get_data().iter().all(|g| g.is_finite()));
+    assert!(layer.gradients().iter().all(|(_, g)| g.get_data().iter().all(|x| x.is_finite())));
+    common::check_gradients(&mut layer, &input);
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let input = common::pattern(vec![2, 3, 4], 0.0);
+    let mut layer = MultiHeadAttention::<f32>::new(4, 2, Accuracy::F32);
+    common::check_gradients(&mut layer, &input);
+
+    let input = common::pattern(vec![2, 4, 6], 1.0);
+    let mut layer = MultiHeadAttention::<f32>::new(6, 3, Accuracy::F32).with_causal(true);
+    layer.set_padding_mask(Some(padding_mask()));
+    common::check_gradients(&mut layer, &input);
+}
+
+#[test]
+fn attention_dropout_only_in_training_and_reuses_its_mask() {
+    let input = common::pattern(vec![1, 5, 4], 0.0);
+    let mut layer = MultiHeadAttention::<f32>::new(4, 2, Accuracy::F32).with_dropout(0.5);
+    let inference = layer.forward(&input, None).unwrap();
+    assert_eq!(layer.forward_train(&input, None).unwrap().get_data(), inference.get_data());
+
+    layer.set_training(true);
+    let weights = common::pattern(vec![1, 5, 4], 0.3);
+    let objective = |layer: &mut MultiHeadAttention<f32>, input: &Tensor<f32>| {
+        rng::set_seed(11);
+        let out = layer.forward_train(input, None).unwrap();
+        out.get_data().iter().zip(weights.get_data()).map(|(a, b)| a * b).sum::<f32>()
+    };
+    let training = layer.forward(&input, None).unwrap();
+    assert_ne!(training.get_data(), inference.get_data());
+
+    objective(&mut layer, &input);
+    let grad = layer.backward(&input, &weights);
+    let eps = 1e-2;
+    for i in [0, 7, 13] {
+        let mut plus = input.get_data().clone();
+        let mut minus = input.get_data().clone();
+        plus[i] += eps;
+        minus[i] -= eps;
+        let plus = Tensor::new(Accuracy::F32, plus, vec![1, 5, 4]);
+        let minus = Tensor::new(Accuracy::F32, minus, vec![1, 5, 4]);
+        let numeric = (objective(&mut layer, &plus) - objective(&mut layer, &minus)) / (2.0 * eps);
+        assert!((grad.get_data()[i] - numeric).abs() < 2e-2, "{} vs {}", grad.get_data()[i], numeric);
+    }
+}
+
+#[test]
+fn padding_mask_changes_per_batch_inside_sequential() {
+    let input = common::pattern(vec![2, 4, 6], 0.0);
+    let grad_output = common::pattern(vec![2, 4, 6], 3.0);
+    let mut model = Sequential::<f32>::new();
+    model.add(MultiHeadAttention::<f32>::new(6, 2, Accuracy::F32));
+
+    let attention = model.layer_mut::<MultiHeadAttention<f32>>(0).unwrap();
+    attention.set_padding_mask(Some(padding_mask()));
+    let masked = model.forward_train(&input, &[None]);
+    let expected = model.backward(&masked, &grad_output);
+
+    // The next batch has no padding; the earlier pass keeps the mask it ran with
+    let attention = model.layer_mut::<MultiHeadAttention<f32>>(0).unwrap();
+    attention.set_padding_mask(None);
+    let unmasked = model.forward_train(&input, &[None]);
+    assert_ne!(unmasked.output().get_data(), masked.output().get_data());
+    assert_eq!(model.backward(&masked, &grad_output).get_data(), expected.get_data());
+    assert_ne!(model.backward(&unmasked, &grad_output).get_data(), expected.get_data());
+}
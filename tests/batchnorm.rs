@@ -0,0 +1,90 @@
+mod common;
+
+use littleflow::layer::batchnorm::{BatchNorm1d, BatchNorm2d};
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::{TrainableLayer, TrainableModel};
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn training_uses_batch_statistics_and_tracks_running_ones() {
+    // Two features with batch means 2 and 20, biased variances 2/3 and 200/3
+    let input = Tensor::new(Accuracy::F32, vec![1.0, 10.0, 2.0, 20.0, 3.0, 30.0], vec![3, 2]);
+    let mut layer = BatchNorm1d::<f32>::new(2, Accuracy::F32).with_momentum(0.5);
+    layer.set_training(true);
+
+    let output = layer.forward_train(&input, None).unwrap();
+    for feature in 0..2 {
+        let column: Vec<f32> = output.get_data().iter().skip(feature).step_by(2).copied().collect();
+        let mean: f32 = column.iter().sum::<f32>() / 3.0;
+        let var: f32 = column.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / 3.0;
+        assert!(mean.abs() < 1e-5);
+        assert!((var - 1.0).abs() < 1e-3);
+    }
+
+    // Running stats move halfway towards the batch mean and unbiased variance
+    assert_eq!(layer.get_running_mean().get_data(), &vec![1.0, 10.0]);
+    let running_var = layer.get_running_var().get_data();
+    assert!((running_var[0] - 1.0).abs() < 1e-5);
+    assert!((running_var[1] - 50.5).abs() < 1e-3);
+
+    // Inference mode normalises with the running statistics
+    layer.set_training(false);
+    let output = layer.forward(&input, None).unwrap();
+    let expected = (3.0 - 1.0) / (1.0f32 + 1e-5).sqrt();
+    assert!((output.get_data()[4] - expected).abs() < 1e-4);
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let mut layer = BatchNorm1d::<f32>::new(3, Accuracy::F32);
+    layer.set_training(true);
+    common::check_gradients(&mut layer, &common::pattern(vec![4, 3, 2], 0.0));
+
+    let mut layer = BatchNorm2d::<f32>::new(2, Accuracy::F32);
+    layer.set_training(true);
+    common::check_gradients(&mut layer, &common::pattern(vec![2, 2, 2, 3], 1.0));
+
+    // In inference mode the statistics are constants
+    let mut layer = BatchNorm2d::<f32>::new(2, Accuracy::F32);
+    common::check_gradients(&mut layer, &common::pattern(vec![2, 2, 2, 3], 2.0));
+}
+
+#[test]
+fn backward_follows_the_input_it_is_given() {
+    let a = common::pattern(vec![4, 3], 0.0);
+    let b = common::pattern(vec![4, 3], 3.0);
+    let grad_output = common::pattern(vec![4, 3], 1.0);
+    let mut layer = BatchNorm1d::<f32>::new(3, Accuracy::F32);
+    layer.set_training(true);
+
+    layer.forward_train(&a, None).unwrap();
+    let expected = layer.backward(&a, &grad_output);
+    layer.forward_train(&b, None).unwrap();
+    assert_eq!(layer.backward(&a, &grad_output).get_data(), expected.get_data());
+}
+
+#[test]
+fn sequential_switches_layers_between_modes() {
+    let input = Tensor::new(Accuracy::F32, vec![1.0, 5.0, 3.0, 7.0], vec![2, 2]);
+    let target = Tensor::new(Accuracy::F32, vec![0.5, 1.5], vec![2, 1]);
+
+    let mut model = Sequential::<f32>::new();
+    model.add(BatchNorm1d::new(2, Accuracy::F32));
+    model.add(DenseLayer::new(2, 1, Accuracy::F32));
+    assert!(!model.is_training());
+
+    // Fresh running stats (mean 0, var 1) leave inference input almost unchanged
+    let inference = model.forward(&input, &[None, None]);
+    model.set_training(true);
+    let training = model.forward(&input, &[None, None]);
+    assert_ne!(inference.get_data(), training.get_data());
+    model.set_training(false);
+
+    model.train(std::slice::from_ref(&input), &[target], &MeanSquaredError, 3, 0.1, &[None, None]);
+    assert!(!model.is_training());
+    assert_eq!(model.parameters()[0].0, "0.gamma");
+    assert_ne!(model.forward(&input, &[None, None]).get_data(), inference.get_data());
+}
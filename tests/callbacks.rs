@@ -0,0 +1,105 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+use littleflow::callbacks::logger::Logger;
+use littleflow::callbacks::{BatchLogs, Callback, EpochLogs};
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::TrainableModel;
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::model::history::TrainingHistory;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[derive(Default)]
+struct Events {
+    begin: Vec<(usize, usize)>,
+    batches: usize,
+    epoch_losses: Vec<f32>,
+    end: usize,
+}
+
+struct Recorder(Rc<RefCell<Events>>);
+
+impl Callback<f32> for Recorder {
+    fn on_train_begin(&mut self, epochs: usize, batches_per_epoch: usize) {
+        self.0.borrow_mut().begin.push((epochs, batches_per_epoch));
+    }
+
+    fn on_batch_end(&mut self, _logs: &BatchLogs<f32>) {
+        self.0.borrow_mut().batches += 1;
+    }
+
+    fn on_epoch_end(&mut self, logs: &EpochLogs<f32>) {
+        assert_eq!(logs.metrics[0].0, "grad_norm");
+        self.0.borrow_mut().epoch_losses.push(logs.loss);
+    }
+
+    fn on_train_end(&mut self, _history: &TrainingHistory<f32>) {
+        self.0.borrow_mut().end += 1;
+    }
+}
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn data() -> (Vec<Tensor<f32>>, Vec<Tensor<f32>>) {
+    let inputs = vec![
+        Tensor::new(Accuracy::F32, vec![1.0, 0.0], vec![1, 2]),
+        Tensor::new(Accuracy::F32, vec![0.0, 1.0], vec![1, 2]),
+        Tensor::new(Accuracy::F32, vec![1.0, 1.0], vec![1, 2]),
+    ];
+    let targets = vec![
+        Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]),
+        Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]),
+        Tensor::new(Accuracy::F32, vec![2.0], vec![1, 1]),
+    ];
+    (inputs, targets)
+}
+
+#[test]
+fn callbacks_receive_every_hook() {
+    let (inputs, targets) = data();
+    let events = Rc::new(RefCell::new(Events::default()));
+
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(2, 1, Accuracy::F32));
+    model.add_callback(Recorder(events.clone()));
+
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 4, 0.05, &[None]);
+
+    let events = events.borrow();
+    assert_eq!(events.begin, vec![(4, 3)]);
+    assert_eq!(events.batches, 12);
+    assert_eq!(events.epoch_losses, history.loss());
+    assert_eq!(events.end, 1);
+}
+
+#[test]
+fn logger_writes_every_n_epochs() {
+    let (inputs, targets) = data();
+    let buffer = SharedBuffer::default();
+
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(2, 1, Accuracy::F32));
+    model.add_callback(Logger::to_writer(buffer.clone()).every(2));
+    model.train(&inputs, &targets, &MeanSquaredError, 5, 0.05, &[None]);
+
+    let output = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("Epoch 2 - Loss: "));
+    assert!(lines[1].starts_with("Epoch 4 - Loss: "));
+}
@@ -0,0 +1,82 @@
+// Shared helpers for the layer gradient tests
+#![allow(dead_code)]
+
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+/// Deterministic, non-symmetric values so gradients do not cancel out
+pub fn pattern(shape: Vec<usize>, offset: f32) -> Tensor<f32> {
+    let size: usize = shape.iter().product();
+    let data = (0..size).map(|i| ((i as f32 + offset) * 0.7).sin()).collect();
+    Tensor::new(Accuracy::F32, data, shape)
+}
+
+fn weighted_sum(layer: &mut dyn TrainableLayer<f32>, input: &Tensor<f32>, weights: &Tensor<f32>) -> f32 {
+    let out = layer.forward_train(input, None).unwrap();
+    out.get_data().iter().zip(weights.get_data()).map(|(a, b)| a * b).sum()
+}
+
+fn set_param(layer: &mut dyn TrainableLayer<f32>, p: usize, i: usize, value: f32) {
+    let mut params = layer.parameters_mut();
+    let param = &mut params[p].1;
+    let mut data = param.get_data().clone();
+    data[i] = value;
+    **param = Tensor::new(Accuracy::F32, data, param.get_shape().clone());
+}
+
+fn assert_close(analytic: f32, numeric: f32, what: &str) {
+    let tol = 2e-2 * (1.0 + numeric.abs().max(analytic.abs()));
+    assert!(
+        (analytic - numeric).abs() < tol,
+        "{}: analytic {} vs numeric {}",
+        what,
+        analytic,
+        numeric
+    );
+}
+
+/// Compares the analytic input and parameter gradients of `layer` with central
+/// finite differences of `sum(forward(input) * w)` for a fixed `w`.
+pub fn check_gradients(layer: &mut dyn TrainableLayer<f32>, input: &Tensor<f32>) {
+    check_gradients_with_eps(layer, input, 1e-2);
+}
+
+/// `check_gradients` with a custom step, e.g. a smaller one for layers with ReLU kinks
+pub fn check_gradients_with_eps(layer: &mut dyn TrainableLayer<f32>, input: &Tensor<f32>, eps: f32) {
+    let out = layer.forward_train(input, None).unwrap();
+    let weights = pattern(out.get_shape().clone(), 0.3);
+
+    layer.zero_grad();
+    let grad_input = layer.backward(input, &weights);
+    assert_eq!(grad_input.get_shape(), input.get_shape());
+
+    for i in 0..input.get_size() {
+        let mut plus = input.get_data().clone();
+        let mut minus = input.get_data().clone();
+        plus[i] += eps;
+        minus[i] -= eps;
+        let plus = Tensor::new(Accuracy::F32, plus, input.get_shape().clone());
+        let minus = Tensor::new(Accuracy::F32, minus, input.get_shape().clone());
+        let numeric = (weighted_sum(layer, &plus, &weights) - weighted_sum(layer, &minus, &weights)) / (2.0 * eps);
+        assert_close(grad_input.get_data()[i], numeric, &format!("input[{}]", i));
+    }
+
+    let grads: Vec<(String, Tensor<f32>)> = layer
+        .gradients()
+        .into_iter()
+        .map(|(name, grad)| (name, grad.clone()))
+        .collect();
+    for (p, (name, grad)) in grads.iter().enumerate() {
+        for i in 0..grad.get_size() {
+            let original = layer.parameters()[p].1.get_data()[i];
+            set_param(layer, p, i, original + eps);
+            let plus = weighted_sum(layer, input, &weights);
+            set_param(layer, p, i, original - eps);
+            let minus = weighted_sum(layer, input, &weights);
+            set_param(layer, p, i, original);
+            let numeric = (plus - minus) / (2.0 * eps);
+            assert_close(grad.get_data()[i], numeric, &format!("{}[{}]", name, i));
+        }
+    }
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use littleflow::layer::batchnorm::BatchNorm1d;
+use littleflow::layer::container::{Concat, Parallel, Residual};
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::dropout::Dropout;
+use littleflow::layer::layernorm::LayerNorm;
+use littleflow::layer::reshape::Flatten;
+use littleflow::layer::trainable::{TrainableLayer, TrainableModel};
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::model::sequential::Sequential;
+use littleflow::rng;
+use littleflow::types::Accuracy;
+
+fn dense(input: usize, output: usize, offset: f32) -> DenseLayer<f32> {
+    let mut layer = DenseLayer::new(input, output, Accuracy::F32);
+    for (i, (_, param)) in layer.parameters_mut().into_iter().enumerate() {
+        let shape = param.get_shape().clone();
+        param.clone_from(&common::pattern(shape, offset + i as f32 * 3.0));
+    }
+    layer
+}
+
+#[test]
+fn residual_adds_the_input_to_the_inner_output() {
+    let input = common::pattern(vec![2, 3], 0.0);
+    let inner = dense(3, 3, 1.0);
+    let expected = inner.forward(&input, None).unwrap().add(&input);
+
+    let layer = Residual::new(inner);
+    assert_eq!(layer.forward(&input, None).unwrap().get_data(), expected.get_data());
+    assert_eq!(layer.parameters().len(), 2);
+    assert!(Residual::new(dense(3, 2, 0.0)).forward(&input, None).is_err());
+}
+
+#[test]
+fn parallel_sums_and_concat_joins_branch_outputs() {
+    let input = common::pattern(vec![2, 3], 0.0);
+    let a = dense(3, 2, 1.0).forward(&input, None).unwrap();
+    let b = dense(3, 2, 2.0).forward(&input, None).unwrap();
+    let c = dense(3, 1, 4.0).forward(&input, None).unwrap();
+
+    let parallel = Parallel::new().with_branch(dense(3, 2, 1.0)).with_branch(dense(3, 2, 2.0));
+    assert_eq!(parallel.forward(&input, None).unwrap().get_data(), a.add(&b).get_data());
+    assert_eq!(parallel.parameters()[2].0, format!("1.{}", parallel.parameters()[0].0.trim_start_matches("0.")));
+    assert!(Parallel::new().with_branch(dense(3, 2, 1.0)).with_branch(dense(3, 1, 2.0)).forward(&input, None).is_err());
+
+    let concat = Concat::new().with_branch(dense(3, 2, 1.0)).with_branch(dense(3, 1, 4.0));
+    let output = concat.forward(&input, None).unwrap();
+    assert_eq!(output.get_shape(), &vec![2, 3]);
+    let a = a.get_data();
+    let c = c.get_data();
+    assert_eq!(output.get_data(), &vec![a[0], a[1], c[0], a[2], a[3], c[1]]);
+
+    // Along the first axis the rows of each branch are stacked instead
+    let concat = Concat::new().with_branch(dense(3, 2, 1.0)).with_branch(dense(3, 2, 2.0)).with_axis(0);
+    let output = concat.forward(&input, None).unwrap();
+    assert_eq!(output.get_shape(), &vec![4, 2]);
+    assert_eq!(&output.get_data()[4..], b.get_data().as_slice());
+    assert!(Concat::<f32>::new().forward(&input, None).is_err());
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let input = common::pattern(vec![2, 3], 0.0);
+
+    let mut layer = Residual::new(dense(3, 3, 1.0));
+    common::check_gradients(&mut layer, &input);
+
+    let mut layer = Residual::new(LayerNorm::new(vec![3], Accuracy::F32));
+    common::check_gradients(&mut layer, &input);
+
+    let mut layer = Parallel::new().with_branch(dense(3, 2, 1.0)).with_branch(dense(3, 2, 2.0));
+    common::check_gradients(&mut layer, &input);
+
+    let mut layer = Concat::new().with_branch(dense(3, 2, 1.0)).with_branch(dense(3, 1, 4.0));
+    common::check_gradients(&mut layer, &input);
+
+    let mut layer = Concat::new()
+        .with_branch(dense(3, 2, 1.0))
+        .with_branch(Residual::new(dense(3, 3, 2.0)))
+        .with_axis(1);
+    common::check_gradients(&mut layer, &input);
+
+    let mut layer = Concat::new().with_branch(Flatten).with_branch(Flatten).with_axis(0);
+    common::check_gradients(&mut layer, &input);
+}
+
+#[test]
+fn containers_forward_mode_and_train_inside_sequential() {
+    let input = common::pattern(vec![4, 2], 0.0);
+    let target = common::pattern(vec![4, 1], 3.0);
+
+    let mut model = Sequential::<f32>::new();
+    model.add(Residual::new(Dropout::new(0.5)));
+    model.add(
+        Concat::new()
+            .with_branch(dense(2, 2, 1.0))
+            .with_branch(Parallel::new().with_branch(dense(2, 1, 2.0)).with_branch(dense(2, 1, 3.0))),
+    );
+    model.add(dense(3, 1, 5.0));
+
+    // Dropout inside the residual is the identity outside training
+    let before = model.forward(&input, &[None, None, None]);
+    assert_eq!(model.forward(&input, &[None, None, None]).get_data(), before.get_data());
+    assert_eq!(model.parameters()[2].0, "1.1.0.weights");
+
+    let loss = |model: &Sequential<f32>| {
+        let output = model.forward(&input, &[None, None, None]);
+        output.get_data().iter().zip(target.get_data()).map(|(o, t)| (o - t).powi(2)).sum::<f32>()
+    };
+    let initial = loss(&model);
+    model.train(std::slice::from_ref(&input), std::slice::from_ref(&target), &MeanSquaredError, 20, 0.002, &[None, None, None]);
+    assert!(!model.is_training());
+    assert!(loss(&model) < initial);
+
+    model.zero_grad();
+    assert!(model.gradients().iter().all(|(_, grad)| grad.get_data().iter().all(|&g| g == 0.0)));
+}
+
+#[test]
+fn containers_keep_the_pass_state_of_their_branches() {
+    rng::set_seed(3);
+    let input = common::pattern(vec![2, 4], 0.0);
+    let grad_output = common::pattern(vec![2, 8], 1.0);
+    let mut model = Sequential::<f32>::new();
+    model.add(
+        Concat::new()
+            .with_branch(Residual::new(Dropout::new(0.5)))
+            .with_branch(Parallel::new().with_branch(Dropout::new(0.5)).with_branch(dense(4, 4, 0.0))),
+    );
+    model.set_training(true);
+
+    // Every pass keeps its own dropout masks and can be backpropagated more than once
+    let first = model.forward_train(&input, &[None]);
+    let expected = model.backward(&first, &grad_output);
+    let second = model.forward_train(&input, &[None]);
+    assert_ne!(first.output().get_data(), second.output().get_data());
+    assert_eq!(model.backward(&first, &grad_output).get_data(), expected.get_data());
+    assert_eq!(model.backward(&first, &grad_output).get_data(), expected.get_data());
+
+    // Concat can also backpropagate without a training pass
+    let mut concat = Concat::new().with_branch(dense(4, 3, 0.0)).with_branch(dense(4, 5, 1.0));
+    assert_eq!(concat.backward(&input, &grad_output).get_shape(), input.get_shape());
+}
+
+#[test]
+fn containers_expose_the_buffers_of_their_branches() {
+    let mut model = Sequential::<f32>::new();
+    model.add(Residual::new(BatchNorm1d::new(4, Accuracy::F32)));
+    model.add(Parallel::new().with_branch(dense(4, 4, 0.0)).with_branch(BatchNorm1d::new(4, Accuracy::F32)));
+    let names: Vec<String> = model.buffers().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["0.running_mean", "0.running_var", "1.1.running_mean", "1.1.running_var"]);
+}
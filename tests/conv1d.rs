@@ -0,0 +1,69 @@
+mod common;
+
+use littleflow::layer::conv1d::{Conv1D, Conv1DConfig, Conv1DPadding};
+use littleflow::layer::initializer::Initializer;
+use littleflow::layer::trainable::{TrainableLayer, TrainableModel};
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+fn ones(config: Conv1DConfig) -> Conv1D<f32> {
+    Conv1D::with_initializers(1, 1, config, Accuracy::F32, &Initializer::Constant(1.0), &Initializer::Zeros)
+}
+
+#[test]
+fn padding_modes() {
+    let input = Tensor::new(Accuracy::F32, vec![1.0, 2.0, 3.0, 4.0, 5.0], vec![1, 1, 5]);
+
+    let valid = ones(Conv1DConfig::new(3)).forward(&input, None).unwrap();
+    assert_eq!(valid.get_data(), &vec![6.0, 9.0, 12.0]);
+
+    let causal = ones(Conv1DConfig::new(3).with_padding(Conv1DPadding::Causal));
+    assert_eq!(causal.forward(&input, None).unwrap().get_data(), &vec![1.0, 3.0, 6.0, 9.0, 12.0]);
+
+    let same = ones(Conv1DConfig::new(3).with_padding(Conv1DPadding::Same));
+    assert_eq!(same.forward(&input, None).unwrap().get_data(), &vec![3.0, 6.0, 9.0, 12.0, 9.0]);
+
+    let strided = ones(Conv1DConfig::new(2).with_padding(Conv1DPadding::Same).with_stride(2));
+    assert_eq!(strided.forward(&input, None).unwrap().get_shape(), &vec![1, 1, 3]);
+
+    let dilated = ones(Conv1DConfig::new(2).with_padding(Conv1DPadding::Causal).with_dilation(2));
+    assert_eq!(dilated.forward(&input, None).unwrap().get_data(), &vec![1.0, 2.0, 4.0, 6.0, 8.0]);
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let input = common::pattern(vec![2, 3, 7], 0.0);
+
+    for config in [
+        Conv1DConfig::new(3).with_padding(Conv1DPadding::Causal).with_dilation(2),
+        Conv1DConfig::new(2).with_padding(Conv1DPadding::Same).with_stride(2),
+    ] {
+        let mut layer = Conv1D::<f32>::with_initializers(
+            3,
+            2,
+            config,
+            Accuracy::F32,
+            &Initializer::HeUniform,
+            &Initializer::Constant(0.1),
+        );
+        common::check_gradients(&mut layer, &input);
+    }
+}
+
+#[test]
+fn trains_inside_sequential() {
+    // Learn a 2-tap moving sum
+    let inputs: Vec<Tensor<f32>> = (0..4).map(|i| common::pattern(vec![1, 1, 6], i as f32)).collect();
+    let targets: Vec<Tensor<f32>> = inputs
+        .iter()
+        .map(|x| ones(Conv1DConfig::new(2).with_padding(Conv1DPadding::Causal)).forward(x, None).unwrap())
+        .collect();
+
+    let mut model = Sequential::<f32>::new();
+    model.add(Conv1D::new(1, 1, Conv1DConfig::new(2).with_padding(Conv1DPadding::Causal), Accuracy::F32));
+
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 200, 0.1, &[None]);
+    assert!(history.loss()[199] < 1e-3);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use littleflow::layer::conv::{Conv2D, Conv2DConfig};
+use littleflow::layer::initializer::Initializer;
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn forward_matches_hand_computed_convolution() {
+    // 1 channel 3x3 input, 2x2 kernel of ones plus bias 1
+    let input = Tensor::new(Accuracy::F32, (1..=9).map(|x| x as f32).collect(), vec![1, 1, 3, 3]);
+    let layer = Conv2D::<f32>::with_initializers(
+        1,
+        1,
+        Conv2DConfig::new((2, 2)),
+        Accuracy::F32,
+        &Initializer::Constant(1.0),
+        &Initializer::Constant(1.0),
+    );
+
+    let out = layer.forward(&input, None).unwrap();
+    assert_eq!(out.get_shape(), &vec![1, 1, 2, 2]);
+    assert_eq!(out.get_data(), &vec![13.0, 17.0, 25.0, 29.0]);
+
+    // Padding 1 and stride 2 keep only the corners of the padded map
+    let layer = Conv2D::<f32>::with_initializers(
+        1,
+        1,
+        Conv2DConfig::new((2, 2)).with_padding((1, 1)).with_stride((2, 2)),
+        Accuracy::F32,
+        &Initializer::Constant(1.0),
+        &Initializer::Zeros,
+    );
+    let out = layer.forward(&input, None).unwrap();
+    assert_eq!(out.get_shape(), &vec![1, 1, 2, 2]);
+    assert_eq!(out.get_data(), &vec![1.0, 5.0, 11.0, 28.0]);
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let config = Conv2DConfig::new((2, 3))
+        .with_stride((2, 1))
+        .with_padding((1, 1))
+        .with_dilation((1, 2))
+        .with_groups(2);
+    let mut layer = Conv2D::<f32>::with_initializers(
+        4,
+        2,
+        config,
+        Accuracy::F32,
+        &Initializer::XavierUniform,
+        &Initializer::Constant(0.1),
+    );
+    let input = common::pattern(vec![2, 4, 4, 5], 0.0);
+
+    let out = layer.forward(&input, None).unwrap();
+    assert_eq!(out.get_shape(), &vec![2, 2, 3, 3]);
+
+    common::check_gradients(&mut layer, &input);
+}
@@ -0,0 +1,118 @@
+use std::rc::Rc;
+
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::{PassState, TrainableLayer, TrainableModel};
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::loss::Loss;
+use littleflow::model::sequential::Sequential;
+use littleflow::optim::sgd::Sgd;
+use littleflow::optim::Optimizer;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+fn build() -> Sequential<f32> {
+    let mut model = Sequential::new();
+    model.add(DenseLayer::new(2, 3, Accuracy::F32));
+    model.add(DenseLayer::new(3, 1, Accuracy::F32));
+    model
+}
+
+#[test]
+fn custom_loop_matches_train() {
+    let inputs = [
+        Tensor::new(Accuracy::F32, vec![1.0, 0.0], vec![1, 2]),
+        Tensor::new(Accuracy::F32, vec![0.0, 1.0], vec![1, 2]),
+    ];
+    let targets = [
+        Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]),
+        Tensor::new(Accuracy::F32, vec![0.0], vec![1, 1]),
+    ];
+    let activations = [None, None];
+    let loss_fn = MeanSquaredError;
+
+    let mut reference = build();
+    let mut model = build();
+    let initial: Vec<Tensor<f32>> = reference.parameters().into_iter().map(|(_, p)| p.clone()).collect();
+    for ((_, param), value) in model.parameters_mut().into_iter().zip(initial) {
+        *param = value;
+    }
+
+    reference.train(&inputs, &targets, &loss_fn, 3, 0.1, &activations);
+
+    let mut optimizer = Sgd::new(0.1);
+    for _ in 0..3 {
+        for (input, target) in inputs.iter().zip(targets.iter()) {
+            model.zero_grad();
+            let cache = model.forward_train(input, &activations);
+            let grad = loss_fn.backward(cache.output(), target);
+            model.backward(&cache, &grad);
+            optimizer.step(&mut model);
+        }
+    }
+
+    for ((_, expected), (_, got)) in reference.parameters().into_iter().zip(model.parameters()) {
+        for (a, b) in expected.get_data().iter().zip(got.get_data()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn backward_returns_input_gradient() {
+    let mut model = build();
+    let input = Tensor::new(Accuracy::F32, vec![0.5, -0.5], vec![1, 2]);
+    let cache = model.forward_train(&input, &[None, None]);
+    assert_eq!(cache.output().get_data(), model.forward(&input, &[None, None]).get_data());
+
+    let grad_input = model.backward(&cache, &Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]));
+    assert_eq!(grad_input.get_shape(), &vec![1, 2]);
+    assert!(model.gradients().iter().any(|(_, g)| g.get_data().iter().any(|&x| x != 0.0)));
+}
+
+/// Multiplies its input by a factor that changes on every training forward
+struct CountingScale {
+    calls: usize,
+    factor: Option<Rc<f32>>,
+}
+
+impl TrainableLayer<f32> for CountingScale {
+    fn forward(&self, input: &Tensor<f32>, _activation: Option<fn(f32) -> f32>) -> Result<Tensor<f32>, String> {
+        Ok(input.clone())
+    }
+
+    fn forward_train(&mut self, input: &Tensor<f32>, _activation: Option<fn(f32) -> f32>) -> Result<Tensor<f32>, String> {
+        self.calls += 1;
+        let factor = self.calls as f32;
+        self.factor = Some(Rc::new(factor));
+        Ok(input.scale(factor))
+    }
+
+    fn backward(&mut self, _input: &Tensor<f32>, grad_output: &Tensor<f32>) -> Tensor<f32> {
+        grad_output.scale(*self.factor.as_deref().unwrap())
+    }
+
+    fn pass_state(&self) -> Option<PassState> {
+        self.factor.clone().map(|factor| factor as PassState)
+    }
+
+    fn set_pass_state(&mut self, state: Option<PassState>) {
+        self.factor = state.and_then(|state| state.downcast::<f32>().ok());
+    }
+}
+
+#[test]
+fn caches_in_flight_keep_their_own_pass() {
+    let mut model = Sequential::new();
+    model.add(CountingScale { calls: 0, factor: None });
+    let input = Tensor::new(Accuracy::F32, vec![1.0, 2.0], vec![1, 2]);
+    let grad = Tensor::new(Accuracy::F32, vec![1.0, 1.0], vec![1, 2]);
+
+    let first = model.forward_train(&input, &[None]);
+    let second = model.forward_train(&input, &[None]);
+    assert_eq!(second.output().get_data(), &vec![2.0, 4.0]);
+
+    assert_eq!(model.backward(&first, &grad).get_data(), &vec![1.0, 1.0]);
+    assert_eq!(model.backward(&second, &grad).get_data(), &vec![2.0, 2.0]);
+    // A cache can be replayed
+    assert_eq!(model.backward(&first, &grad).get_data(), &vec![1.0, 1.0]);
+}
@@ -0,0 +1,104 @@
+use littleflow::layer::dropout::{AlphaDropout, Dropout};
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::model::sequential::Sequential;
+use littleflow::rng;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+fn ones(size: usize) -> Tensor<f32> {
+    Tensor::new(Accuracy::F32, vec![1.0; size], vec![1, size])
+}
+
+#[test]
+fn dropout_scales_kept_units_and_reuses_the_mask() {
+    rng::set_seed(7);
+    let input = ones(1000);
+    let mut layer = Dropout::<f32>::new(0.25);
+
+    // Identity at inference
+    assert_eq!(layer.forward_train(&input, None).unwrap().get_data(), input.get_data());
+
+    layer.set_training(true);
+    let output = layer.forward_train(&input, None).unwrap();
+    let kept = output.get_data().iter().filter(|&&x| x != 0.0).count();
+    assert!((650..850).contains(&kept));
+    assert!(output.get_data().iter().all(|&x| x == 0.0 || (x - 4.0 / 3.0).abs() < 1e-6));
+
+    // Backward routes the gradient through the same units
+    let grad = layer.backward(&input, &input);
+    assert_eq!(grad.get_data(), output.get_data());
+
+    // Same seed, same mask
+    rng::set_seed(7);
+    let mut again = Dropout::<f32>::new(0.25);
+    again.set_training(true);
+    assert_eq!(again.forward_train(&input, None).unwrap().get_data(), output.get_data());
+}
+
+#[test]
+fn backward_uses_the_mask_of_the_pass_it_is_given() {
+    rng::set_seed(11);
+    let input = ones(100);
+    let mut layer = Dropout::<f32>::new(0.5);
+    layer.set_training(true);
+
+    let first = layer.forward_train(&input, None).unwrap();
+    let first_state = layer.pass_state();
+    let second = layer.forward_train(&input, None).unwrap();
+    assert_ne!(first.get_data(), second.get_data());
+
+    // The mask stays on the layer, so the last pass can be backpropagated twice
+    assert_eq!(layer.backward(&input, &input).get_data(), second.get_data());
+    assert_eq!(layer.backward(&input, &input).get_data(), second.get_data());
+
+    layer.set_pass_state(first_state);
+    assert_eq!(layer.backward(&input, &input).get_data(), first.get_data());
+
+    let mut alpha = AlphaDropout::<f32>::new(0.5);
+    alpha.set_training(true);
+    alpha.forward_train(&input, None).unwrap();
+    let grad = alpha.backward(&input, &input);
+    assert_eq!(alpha.backward(&input, &input).get_data(), grad.get_data());
+}
+
+#[test]
+fn alpha_dropout_keeps_mean_and_variance() {
+    rng::set_seed(3);
+    let size = 20000;
+    let data: Vec<f32> = (0..size)
+        .map(|i| {
+            // Deterministic standard normal samples (inverse of a Box-Muller pair)
+            let u = (i as f32 + 0.5) / size as f32;
+            let v = ((i * 7919) % size) as f32 / size as f32;
+            (-2.0 * u.ln()).sqrt() * (2.0 * std::f32::consts::PI * v).cos()
+        })
+        .collect();
+    let input = Tensor::new(Accuracy::F32, data, vec![1, size]);
+
+    let mut layer = AlphaDropout::<f32>::new(0.2);
+    layer.set_training(true);
+    let output = layer.forward_train(&input, None).unwrap();
+    let mean: f32 = output.get_data().iter().sum::<f32>() / size as f32;
+    let var: f32 = output.get_data().iter().map(|x| (x - mean).powi(2)).sum::<f32>() / size as f32;
+    assert!(mean.abs() < 0.05, "mean {}", mean);
+    assert!((var - 1.0).abs() < 0.1, "var {}", var);
+
+    let grad = layer.backward(&input, &ones(size));
+    assert!(grad.get_data().contains(&0.0));
+
+    layer.set_training(false);
+    assert_eq!(layer.forward(&input, None).unwrap().get_data(), input.get_data());
+}
+
+#[test]
+fn sequential_mode_controls_dropout() {
+    let input = ones(100);
+    let mut model = Sequential::<f32>::new();
+    model.add(Dropout::new(0.5));
+
+    assert_eq!(model.forward(&input, &[None]).get_data(), input.get_data());
+    model.set_training(true);
+    let cache = model.forward_train(&input, &[None]);
+    assert_ne!(cache.output().get_data(), input.get_data());
+    assert_eq!(model.backward(&cache, &input).get_data(), cache.output().get_data());
+}
@@ -0,0 +1,108 @@
+use littleflow::layer::batchnorm::BatchNorm1d;
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::TrainableModel;
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::model::early_stopping::{EarlyStopping, Monitor};
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+fn copy_parameters(from: &Sequential<f32>, to: &mut Sequential<f32>) {
+    let values: Vec<Tensor<f32>> = from.parameters().into_iter().map(|(_, p)| p.clone()).collect();
+    for ((_, param), value) in to.parameters_mut().into_iter().zip(values) {
+        *param = value;
+    }
+}
+
+#[test]
+fn stops_on_diverging_loss_and_restores_best_epoch() {
+    // A learning rate this large makes the loss explode after the first update
+    let inputs = [Tensor::new(Accuracy::F32, vec![10.0, 10.0], vec![1, 2])];
+    let targets = [Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1])];
+
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(2, 1, Accuracy::F32));
+    model.set_early_stopping(Some(EarlyStopping::new(Monitor::Loss, 2, 0.0)));
+
+    let mut reference = Sequential::<f32>::new();
+    reference.add(DenseLayer::new(2, 1, Accuracy::F32));
+    copy_parameters(&model, &mut reference);
+
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 20, 1.0, &[None]);
+    reference.train(&inputs, &targets, &MeanSquaredError, 1, 1.0, &[None]);
+
+    let early_stopping = model.early_stopping().unwrap();
+    assert_eq!(history.epochs(), 3);
+    assert_eq!(early_stopping.best_epoch(), Some(1));
+    assert_eq!(early_stopping.stopped_epoch(), Some(3));
+    assert_eq!(early_stopping.best(), Some(history.loss()[0]));
+
+    for ((_, expected), (_, got)) in reference.parameters().into_iter().zip(model.parameters()) {
+        assert_eq!(expected.get_data(), got.get_data());
+    }
+}
+
+#[test]
+fn restores_the_buffers_of_the_best_epoch() {
+    let inputs = [Tensor::new(Accuracy::F32, vec![10.0, 10.0, -5.0, 2.0, 3.0, -8.0], vec![3, 2])];
+    let targets = [Tensor::new(Accuracy::F32, vec![1.0, -1.0, 0.5], vec![3, 1])];
+    let build = || {
+        let mut model = Sequential::<f32>::new();
+        model.add(DenseLayer::new(2, 2, Accuracy::F32));
+        model.add(BatchNorm1d::new(2, Accuracy::F32).with_momentum(0.5));
+        model.add(DenseLayer::new(2, 1, Accuracy::F32));
+        model
+    };
+
+    let mut model = build();
+    model.set_early_stopping(Some(EarlyStopping::new(Monitor::Loss, 2, 0.0)));
+    let mut reference = build();
+    copy_parameters(&model, &mut reference);
+
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 20, 2.0, &[None, None, None]);
+    let best_epoch = model.early_stopping().unwrap().best_epoch().unwrap();
+    assert!(best_epoch < history.epochs());
+    reference.train(&inputs, &targets, &MeanSquaredError, best_epoch, 2.0, &[None, None, None]);
+
+    assert_eq!(model.buffers().len(), 2);
+    for ((_, expected), (_, got)) in reference.buffers().into_iter().zip(model.buffers()) {
+        assert_eq!(expected.get_data(), got.get_data());
+    }
+    for ((_, expected), (_, got)) in reference.parameters().into_iter().zip(model.parameters()) {
+        assert_eq!(expected.get_data(), got.get_data());
+    }
+}
+
+#[test]
+fn runs_all_epochs_while_improving() {
+    let inputs = [Tensor::new(Accuracy::F32, vec![1.0, 0.5], vec![1, 2])];
+    let targets = [Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1])];
+
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(2, 1, Accuracy::F32));
+    model.set_early_stopping(Some(EarlyStopping::new(Monitor::Loss, 1, 0.0)));
+
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 10, 0.1, &[None]);
+
+    assert_eq!(history.epochs(), 10);
+    assert_eq!(model.early_stopping().unwrap().stopped_epoch(), None);
+    assert_eq!(model.early_stopping().unwrap().best_epoch(), Some(10));
+}
+
+#[test]
+fn val_loss_without_validation_data_is_skipped() {
+    let inputs = [Tensor::new(Accuracy::F32, vec![10.0, 10.0], vec![1, 2])];
+    let targets = [Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1])];
+
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(2, 1, Accuracy::F32));
+    model.set_early_stopping(Some(EarlyStopping::new(Monitor::ValLoss, 1, 0.0)));
+
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 5, 1.0, &[None]);
+
+    assert_eq!(history.epochs(), 5);
+    assert_eq!(model.early_stopping().unwrap().best_epoch(), None);
+    assert_eq!(model.early_stopping().unwrap().stopped_epoch(), None);
+    assert_eq!(history.warnings().len(), 1);
+    assert!(history.warnings()[0].contains("ValLoss"));
+}
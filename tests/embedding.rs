@@ -0,0 +1,108 @@
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::embedding::Embedding;
+use littleflow::layer::initializer::Initializer;
+use littleflow::layer::reshape::Flatten;
+use littleflow::layer::trainable::{TrainableLayer, TrainableModel};
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::model::sequential::Sequential;
+use littleflow::optim::sgd::Sgd;
+use littleflow::optim::Optimizer;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+fn table() -> Tensor<f32> {
+    Tensor::new(Accuracy::F32, (0..8).map(|x| x as f32).collect(), vec![4, 2])
+}
+
+#[test]
+fn looks_up_rows_and_accumulates_only_touched_ones() {
+    let mut layer = Embedding::with_initializer(4, 2, Accuracy::F32, &Initializer::FromTensor(table()));
+    let input = Tensor::new(Accuracy::F32, vec![3.0, 1.0, 3.0], vec![1, 3]);
+
+    let output = layer.forward_train(&input, None).unwrap();
+    assert_eq!(output.get_shape(), &vec![1, 3, 2]);
+    assert_eq!(output.get_data(), &vec![6.0, 7.0, 2.0, 3.0, 6.0, 7.0]);
+
+    let grad_output = Tensor::new(Accuracy::F32, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![1, 3, 2]);
+    let grad_input = layer.backward(&input, &grad_output);
+    assert_eq!(grad_input.get_data(), &vec![0.0; 3]);
+    assert_eq!(layer.gradients()[0].1.get_data(), &vec![0.0, 0.0, 3.0, 4.0, 0.0, 0.0, 6.0, 8.0]);
+    assert_eq!(layer.get_touched_rows(), vec![1, 3]);
+
+    layer.zero_grad();
+    assert_eq!(layer.gradients()[0].1.get_data(), &vec![0.0; 8]);
+    assert!(layer.get_touched_rows().is_empty());
+
+    assert!(layer.forward(&Tensor::new(Accuracy::F32, vec![4.0], vec![1, 1]), None).is_err());
+    assert!(layer.forward(&Tensor::new(Accuracy::F32, vec![0.5], vec![1, 1]), None).is_err());
+}
+
+#[test]
+fn padding_index_and_max_norm() {
+    let mut layer = Embedding::with_initializer(4, 2, Accuracy::F32, &Initializer::FromTensor(table()))
+        .with_padding_idx(0)
+        .with_max_norm(5.0);
+    let input = Tensor::new(Accuracy::F32, vec![0.0, 1.0, 2.0], vec![3]);
+
+    // Row 2 (4, 5) has norm above 5 and is rescaled; row 1 (2, 3) is left alone
+    let output = layer.forward_train(&input, None).unwrap();
+    assert_eq!(&output.get_data()[..4], &[0.0, 0.0, 2.0, 3.0]);
+    let row = &layer.get_weights().get_data()[4..6];
+    assert!(((row[0] * row[0] + row[1] * row[1]).sqrt() - 5.0).abs() < 1e-4);
+    assert_eq!(&layer.get_weights().get_data()[6..], &[6.0, 7.0]);
+
+    layer.backward(&input, &Tensor::new(Accuracy::F32, vec![1.0; 6], vec![3, 2]));
+    assert_eq!(&layer.gradients()[0].1.get_data()[..2], &[0.0, 0.0]);
+    assert_eq!(layer.get_touched_rows(), vec![1, 2]);
+}
+
+#[test]
+fn trains_inside_sequential() {
+    let inputs = [
+        Tensor::new(Accuracy::F32, vec![0.0, 1.0], vec![1, 2]),
+        Tensor::new(Accuracy::F32, vec![2.0, 3.0], vec![1, 2]),
+    ];
+    let targets = [
+        Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]),
+        Tensor::new(Accuracy::F32, vec![-1.0], vec![1, 1]),
+    ];
+
+    let mut model = Sequential::<f32>::new();
+    model.add(Embedding::new(5, 3, Accuracy::F32));
+    model.add(Flatten);
+    model.add(DenseLayer::new(6, 1, Accuracy::F32));
+
+    let before: Vec<f32> = model.parameters()[0].1.get_data().clone();
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 50, 0.1, &[None, None, None]);
+    assert!(history.loss().last().unwrap() < &history.loss()[0]);
+
+    // Row 4 is never looked up
+    assert_eq!(&model.parameters()[0].1.get_data()[12..], &before[12..]);
+}
+
+#[test]
+fn optimizer_updates_only_the_touched_rows() {
+    let mut model = Sequential::<f32>::new();
+    model.add(Embedding::with_initializer(4, 2, Accuracy::F32, &Initializer::FromTensor(table())));
+    model.add(Flatten);
+    model.add(DenseLayer::new(4, 1, Accuracy::F32));
+
+    let input = Tensor::new(Accuracy::F32, vec![3.0, 1.0], vec![1, 2]);
+    let cache = model.forward_train(&input, &[None, None, None]);
+    model.backward(&cache, &Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]));
+
+    let rows = model.gradient_rows();
+    assert_eq!(rows[0], Some(vec![1, 3]));
+    assert!(rows[1..].iter().all(Option::is_none));
+
+    let grad = model.gradients()[0].1.clone();
+    let before = model.parameters()[0].1.clone();
+    Sgd::new(0.5).step(&mut model);
+
+    let after = model.parameters()[0].1.get_data();
+    for (i, (&b, &a)) in before.get_data().iter().zip(after).enumerate() {
+        assert_eq!(a, b - 0.5 * grad.get_data()[i]);
+    }
+    assert_eq!(&after[..2], &before.get_data()[..2]);
+    assert_eq!(&after[4..6], &before.get_data()[4..6]);
+}
@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use littleflow::callbacks::{Callback, EpochLogs};
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::TrainableModel;
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::metrics::accuracy::BinaryAccuracy;
+use littleflow::metrics::mae::MeanAbsoluteError;
+use littleflow::metrics::Metric;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+fn scalar(x: f32) -> Tensor<f32> {
+    Tensor::new(Accuracy::F32, vec![x], vec![1, 1])
+}
+
+#[test]
+fn evaluate_reports_mean_loss_and_metrics() {
+    // y = 2x with zero bias
+    let mut layer = DenseLayer::<f32>::new(1, 1, Accuracy::F32);
+    layer.set_weights(&scalar(2.0));
+    let mut model = Sequential::new();
+    model.add(layer);
+
+    let inputs = [scalar(1.0), scalar(0.0)];
+    let targets = [scalar(1.0), scalar(0.0)];
+    let metrics: Vec<Box<dyn Metric<f32>>> = vec![Box::new(MeanAbsoluteError), Box::new(BinaryAccuracy::new())];
+
+    let evaluation = model.evaluate(&inputs, &targets, &MeanSquaredError, &metrics, &[None]);
+
+    // errors are 1 and 0
+    assert_eq!(evaluation.loss, 0.5);
+    assert_eq!(evaluation.metrics[0], ("mae".to_string(), 0.5));
+    assert_eq!(evaluation.metrics[1], ("accuracy".to_string(), 1.0));
+}
+
+struct ValRecorder(Rc<RefCell<Vec<EpochLogs<f32>>>>);
+
+impl Callback<f32> for ValRecorder {
+    fn on_epoch_end(&mut self, logs: &EpochLogs<f32>) {
+        self.0.borrow_mut().push(EpochLogs {
+            epoch: logs.epoch,
+            loss: logs.loss,
+            val_loss: logs.val_loss,
+            metrics: logs.metrics.clone(),
+        });
+    }
+}
+
+#[test]
+fn validation_split_is_scored_every_epoch() {
+    let inputs: Vec<Tensor<f32>> = (0..10).map(|i| scalar(i as f32 / 10.0)).collect();
+    let targets: Vec<Tensor<f32>> = (0..10).map(|i| scalar(i as f32 / 5.0)).collect();
+    let logs = Rc::new(RefCell::new(Vec::new()));
+
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(1, 1, Accuracy::F32));
+    model.add_metric(MeanAbsoluteError);
+    model.set_validation_split(Some(0.2));
+    model.add_callback(ValRecorder(logs.clone()));
+
+    model.train(&inputs, &targets, &MeanSquaredError, 3, 0.1, &[None]);
+
+    let logs = logs.borrow();
+    assert_eq!(logs.len(), 3);
+    for epoch in logs.iter() {
+        let names: Vec<&str> = epoch.metrics.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["mae", "val_mae", "grad_norm"]);
+    }
+
+    // The last epoch's validation loss must match an explicit evaluation of the last two samples
+    let metrics: Vec<Box<dyn Metric<f32>>> = vec![];
+    let evaluation = model.evaluate(&inputs[8..], &targets[8..], &MeanSquaredError, &metrics, &[None]);
+    assert_eq!(logs[2].val_loss, Some(evaluation.loss));
+}
@@ -0,0 +1,48 @@
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::TrainableModel;
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::loss::Loss;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn accumulated_update_matches_averaged_gradient() {
+    let inputs = [
+        Tensor::new(Accuracy::F32, vec![1.0, 0.0], vec![1, 2]),
+        Tensor::new(Accuracy::F32, vec![0.0, 1.0], vec![1, 2]),
+    ];
+    let targets = [
+        Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]),
+        Tensor::new(Accuracy::F32, vec![-1.0], vec![1, 1]),
+    ];
+    let learning_rate = 0.1;
+    let loss_fn = MeanSquaredError;
+
+    let layer = DenseLayer::<f32>::new(2, 1, Accuracy::F32);
+    let mut reference = DenseLayer::<f32>::new(2, 1, Accuracy::F32);
+    reference.set_weights(layer.get_weights());
+
+    // Both micro-batches see the same initial weights, then one averaged update
+    let mut sum_w = Tensor::zeros(Accuracy::F32, vec![2, 1]);
+    let mut sum_b = Tensor::zeros(Accuracy::F32, vec![1]);
+    for (input, target) in inputs.iter().zip(targets.iter()) {
+        let pred = reference.forward(input, None).unwrap();
+        let (_, grad_w, grad_b) = reference.backward(input, &loss_fn.backward(&pred, target));
+        sum_w = sum_w.add(&grad_w);
+        sum_b = sum_b.add(&grad_b);
+    }
+    reference.set_weights(&reference.get_weights().sub(&sum_w.scale(learning_rate / 2.0)));
+    reference.update_bias(&reference.get_bias().sub(&sum_b.scale(learning_rate / 2.0)));
+
+    let mut model = Sequential::<f32>::new();
+    model.add(layer);
+    model.set_accumulation_steps(2);
+    model.train(&inputs, &targets, &loss_fn, 1, learning_rate, &[None]);
+
+    for input in &inputs {
+        let expected = reference.forward(input, None).unwrap();
+        let got = model.forward(input, &[None]);
+        assert!((expected.get_data()[0] - got.get_data()[0]).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,44 @@
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::TrainableModel;
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::model::sequential::Sequential;
+use littleflow::optim::clip::{global_norm, GradientClipping};
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn clip_by_value_and_global_norm() {
+    let mut grads: Vec<Tensor<f32>> = vec![
+        Tensor::new(Accuracy::F32, vec![3.0, -4.0], vec![1, 2]),
+        Tensor::new(Accuracy::F32, vec![0.5], vec![1]),
+    ];
+    let norm = GradientClipping::Value(1.0).apply(&mut grads);
+    assert!((norm - 25.25f32.sqrt()).abs() < 1e-6);
+    assert_eq!(grads[0].get_data(), &vec![1.0, -1.0]);
+    assert_eq!(grads[1].get_data(), &vec![0.5]);
+
+    let mut grads: Vec<Tensor<f32>> = vec![
+        Tensor::new(Accuracy::F32, vec![3.0], vec![1]),
+        Tensor::new(Accuracy::F32, vec![4.0], vec![1]),
+    ];
+    let norm = GradientClipping::GlobalNorm(1.0).apply(&mut grads);
+    assert_eq!(norm, 5.0);
+    assert!((global_norm(&grads) - 1.0).abs() < 1e-6);
+    assert!((grads[0].get_data()[0] - 0.6).abs() < 1e-6);
+}
+
+#[test]
+fn train_records_pre_clip_norm() {
+    let inputs = [Tensor::new(Accuracy::F32, vec![100.0, -100.0], vec![1, 2])];
+    let targets = [Tensor::new(Accuracy::F32, vec![1000.0], vec![1, 1])];
+
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(2, 1, Accuracy::F32));
+    model.set_gradient_clipping(Some(GradientClipping::GlobalNorm(1.0)));
+
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 5, 0.01, &[None]);
+
+    assert_eq!(history.grad_norms().len(), 5);
+    assert!(history.grad_norms()[0] > 1.0);
+    assert!(history.loss().iter().all(|loss| loss.is_finite()));
+}
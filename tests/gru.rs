@@ -0,0 +1,118 @@
+mod common;
+
+use littleflow::layer::gru::GRU;
+use littleflow::layer::initializer::Initializer;
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn variants_and_output_shapes() {
+    let input = common::pattern(vec![2, 5, 3], 0.0);
+    let init = |layer: GRU<f32>| {
+        let mut layer = layer;
+        for (p, (_, param)) in layer.parameters_mut().into_iter().enumerate() {
+            *param = common::pattern(param.get_shape().clone(), p as f32);
+        }
+        layer
+    };
+
+    let original = init(GRU::new(3, 4, Accuracy::F32));
+    let reset_after = init(GRU::new(3, 4, Accuracy::F32).with_reset_after(true));
+    assert_eq!(original.parameters().len(), 3);
+    assert_eq!(reset_after.parameters().len(), 4);
+
+    let a = original.forward(&input, None).unwrap();
+    let b = reset_after.forward(&input, None).unwrap();
+    assert_eq!(a.get_shape(), &vec![2, 4]);
+    assert_ne!(a.get_data(), b.get_data());
+
+    // The final state comes next to the output, not inside it
+    let mut all = original.with_return_sequences(true).with_return_state(true);
+    let (out, state) = all.forward_with_state(&input, None).unwrap();
+    assert_eq!(out.get_shape(), &vec![2, 5, 4]);
+    assert_eq!(&out.get_data()[16..20], &a.get_data()[..4]);
+    assert_eq!(state.get_data(), a.get_data());
+
+    all.forward_train(&input, None).unwrap();
+    assert_eq!(all.get_final_state().unwrap().get_data(), a.get_data());
+}
+
+#[test]
+fn saturated_update_gate_keeps_state() {
+    // A huge update-gate bias makes z = 1, so h stays at the initial state
+    let mut layer = GRU::<f32>::with_initializers(
+        2,
+        2,
+        Accuracy::F32,
+        &Initializer::Constant(0.0),
+        &Initializer::Constant(0.0),
+        &Initializer::Constant(0.0),
+    );
+    layer.parameters_mut()[2].1.clone_from(&Tensor::new(
+        Accuracy::F32,
+        vec![0.0, 0.0, 50.0, 50.0, 0.0, 0.0],
+        vec![6],
+    ));
+    let state = common::pattern(vec![1, 2], 2.0);
+    layer.set_initial_state(Some(state.clone()));
+    let out = layer.forward(&common::pattern(vec![1, 3, 2], 0.0), None).unwrap();
+    assert_eq!(out.get_data(), state.get_data());
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let input = common::pattern(vec![2, 4, 3], 0.0);
+    for reset_after in [false, true] {
+        let mut layer = GRU::<f32>::new(3, 3, Accuracy::F32).with_reset_after(reset_after);
+        for (p, (_, param)) in layer.parameters_mut().into_iter().enumerate().skip(2) {
+            *param = common::pattern(param.get_shape().clone(), p as f32);
+        }
+        common::check_gradients(&mut layer, &input);
+
+        let mut layer = GRU::<f32>::new(3, 3, Accuracy::F32)
+            .with_reset_after(reset_after)
+            .with_return_sequences(true)
+            .with_return_state(true);
+        layer.set_initial_state(Some(common::pattern(vec![2, 3], 5.0)));
+        common::check_gradients(&mut layer, &input);
+    }
+}
+
+#[test]
+fn final_state_gradient_flows_into_the_input() {
+    let input = common::pattern(vec![2, 3, 2], 2.0);
+    let weights = common::pattern(vec![2, 3], 4.0);
+    let mut layer = GRU::<f32>::new(2, 3, Accuracy::F32)
+        .with_reset_after(true)
+        .with_return_sequences(true)
+        .with_return_state(true);
+
+    // Loss = sum(final state * weights), so the output itself gets no gradient
+    layer.set_final_state_grad(Some(weights.clone()));
+    layer.forward_train(&input, None).unwrap();
+    let grad = layer.backward(&input, &Tensor::zeros(Accuracy::F32, vec![2, 3, 3]));
+
+    let eps = 1e-2;
+    let objective = |delta: f32| {
+        let mut data = input.get_data().clone();
+        data[5] += delta;
+        let input = Tensor::new(Accuracy::F32, data, vec![2, 3, 2]);
+        let (_, state) = layer.forward_with_state(&input, None).unwrap();
+        state.get_data().iter().zip(weights.get_data()).map(|(a, b)| a * b).sum::<f32>()
+    };
+    let numeric = (objective(eps) - objective(-eps)) / (2.0 * eps);
+    assert!((grad.get_data()[5] - numeric).abs() < 1e-2, "{} vs {}", grad.get_data()[5], numeric);
+}
+
+#[test]
+fn initial_state_is_reachable_inside_sequential() {
+    let input = common::pattern(vec![1, 3, 2], 0.0);
+    let mut model = Sequential::<f32>::new();
+    model.add(GRU::<f32>::new(2, 2, Accuracy::F32));
+
+    let from_zero = model.forward(&input, &[None]);
+    model.layer_mut::<GRU<f32>>(0).unwrap().set_initial_state(Some(common::pattern(vec![1, 2], 2.0)));
+    assert_ne!(model.forward(&input, &[None]).get_data(), from_zero.get_data());
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::TrainableModel;
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::metrics::mae::MeanAbsoluteError;
+use littleflow::model::early_stopping::Monitor;
+use littleflow::model::history::TrainingHistory;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn best_epoch_and_exports() {
+    let mut history = TrainingHistory::<f32>::new();
+    let metrics = |mae: f32| vec![("mae".to_string(), mae)];
+    history.push_epoch(1.0, Some(0.75), &metrics(0.5), 0.1, 2.0, Duration::from_millis(500));
+    history.push_epoch(0.5, Some(f32::NAN), &metrics(0.25), 0.1, 1.0, Duration::from_millis(250));
+    history.push_epoch(0.25, Some(1.0), &metrics(0.125), 0.1, 0.5, Duration::from_millis(250));
+
+    assert_eq!(history.best_epoch(Monitor::Loss), Some(3));
+    assert_eq!(history.best_epoch(Monitor::ValLoss), Some(1));
+    assert_eq!(history.metric("mae"), Some(&[0.5, 0.25, 0.125][..]));
+    assert_eq!(history.total_time(), Duration::from_secs(1));
+
+    let csv = history.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "epoch,loss,val_loss,mae,learning_rate,grad_norm,time_secs");
+    assert_eq!(lines[1], "1,1,0.75,0.5,0.1,2,0.5");
+    assert_eq!(lines.len(), 4);
+
+    let json = history.to_json();
+    assert!(json.starts_with("{\"loss\":[1,0.5,0.25],\"val_loss\":[0.75,null,1],\"mae\":[0.5,0.25,0.125]"));
+    assert!(json.ends_with("\"time_secs\":[0.5,0.25,0.25]}"));
+}
+
+#[test]
+fn exports_quote_names_and_agree_on_non_finite_values() {
+    let mut history = TrainingHistory::<f32>::new();
+    let metrics = vec![("f1, \"macro\"".to_string(), f32::INFINITY)];
+    history.push_epoch(f32::NAN, None, &metrics, 0.1, 1.0, Duration::from_millis(500));
+
+    let csv = history.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "epoch,loss,val_loss,\"f1, \"\"macro\"\"\",learning_rate,grad_norm,time_secs");
+    assert_eq!(lines[1], "1,,,,0.1,1,0.5");
+
+    let json = history.to_json();
+    assert!(json.starts_with("{\"loss\":[null],\"val_loss\":[null],\"f1, \\\"macro\\\"\":[null]"));
+}
+
+#[test]
+fn train_records_mean_loss_and_metrics() {
+    let inputs = [
+        Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]),
+        Tensor::new(Accuracy::F32, vec![2.0], vec![1, 1]),
+    ];
+    let targets = [
+        Tensor::new(Accuracy::F32, vec![2.0], vec![1, 1]),
+        Tensor::new(Accuracy::F32, vec![4.0], vec![1, 1]),
+    ];
+
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(1, 1, Accuracy::F32));
+    model.add_metric(MeanAbsoluteError);
+    model.set_validation_data(inputs.to_vec(), targets.to_vec());
+
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 4, 0.05, &[None]);
+
+    assert_eq!(history.epochs(), 4);
+    assert_eq!(history.learning_rates(), &[0.05; 4]);
+    assert_eq!(history.metric_names(), vec!["mae", "val_mae"]);
+    assert!(history.val_loss().iter().all(|v| v.is_some()));
+    assert_eq!(history.epoch_times().len(), 4);
+    assert!(history.loss()[3] < history.loss()[0]);
+}
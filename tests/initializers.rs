@@ -0,0 +1,59 @@
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::initializer::Initializer;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+fn init(initializer: Initializer<f32>, shape: Vec<usize>, fan_in: usize, fan_out: usize) -> Tensor<f32> {
+    initializer.initialize_with_rng(shape, fan_in, fan_out, Accuracy::F32, &mut StdRng::seed_from_u64(3))
+}
+
+#[test]
+fn distributions_respect_their_bounds() {
+    let limit = (6.0f32 / (40.0 + 60.0)).sqrt();
+    let w = init(Initializer::XavierUniform, vec![40, 60], 40, 60);
+    assert!(w.get_data().iter().all(|x| x.abs() <= limit));
+
+    let w = init(Initializer::TruncatedNormal { mean: 1.0, std: 0.5 }, vec![1000], 1, 1);
+    assert!(w.get_data().iter().all(|x| (x - 1.0).abs() <= 1.0));
+
+    let w = init(Initializer::HeNormal, vec![200, 100], 200, 100);
+    let n = w.get_size() as f32;
+    let mean = w.get_data().iter().sum::<f32>() / n;
+    let std = (w.get_data().iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / n).sqrt();
+    assert!((std - (2.0f32 / 200.0).sqrt()).abs() < 0.01);
+}
+
+#[test]
+fn orthogonal_has_orthonormal_columns() {
+    let w = init(Initializer::Orthogonal { gain: 1.0 }, vec![6, 4], 6, 4);
+    let gram = w.transpose().matmul(&w);
+    for i in 0..4 {
+        for j in 0..4 {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            assert!((gram.get_data()[i * 4 + j] - expected).abs() < 1e-5);
+        }
+    }
+
+    // Wide matrices get orthonormal rows instead
+    let w = init(Initializer::Orthogonal { gain: 2.0 }, vec![2, 5], 2, 5);
+    let gram = w.matmul(&w.transpose());
+    assert!((gram.get_data()[0] - 4.0).abs() < 1e-4);
+    assert!(gram.get_data()[1].abs() < 1e-4);
+}
+
+#[test]
+fn dense_layer_uses_selected_initializers() {
+    let weights = Tensor::new(Accuracy::F32, vec![1.0, 2.0, 3.0, 4.0], vec![2, 2]);
+    let layer = DenseLayer::<f32>::with_initializers(
+        2,
+        2,
+        Accuracy::F32,
+        &Initializer::FromTensor(weights),
+        &Initializer::Constant(0.5),
+    );
+
+    assert_eq!(layer.get_weights().get_data(), &vec![1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(layer.get_bias().get_data(), &vec![0.5, 0.5]);
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use littleflow::layer::layernorm::{LayerNorm, RMSNorm};
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn normalises_each_sample_over_trailing_dims() {
+    let input = Tensor::new(Accuracy::F32, vec![1.0, 2.0, 3.0, 4.0, 10.0, 10.0, 10.0, 30.0], vec![2, 4]);
+
+    let layer = LayerNorm::<f32>::new(vec![4], Accuracy::F32);
+    let output = layer.forward(&input, None).unwrap();
+    for sample in output.get_data().chunks(4) {
+        let mean: f32 = sample.iter().sum::<f32>() / 4.0;
+        let var: f32 = sample.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / 4.0;
+        assert!(mean.abs() < 1e-5);
+        assert!((var - 1.0).abs() < 1e-3);
+    }
+
+    let layer = RMSNorm::<f32>::new(vec![4], Accuracy::F32);
+    let output = layer.forward(&input, None).unwrap();
+    for sample in output.get_data().chunks(4) {
+        let mean_square: f32 = sample.iter().map(|x| x * x).sum::<f32>() / 4.0;
+        assert!((mean_square - 1.0).abs() < 1e-4);
+    }
+
+    assert!(LayerNorm::<f32>::new(vec![3], Accuracy::F32).forward(&input, None).is_err());
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let input = common::pattern(vec![3, 2, 4], 0.0);
+
+    let mut layer = LayerNorm::<f32>::new(vec![2, 4], Accuracy::F32);
+    layer.parameters_mut()[0].1.clone_from(&common::pattern(vec![2, 4], 5.0));
+    common::check_gradients(&mut layer, &input);
+
+    let mut layer = RMSNorm::<f32>::new(vec![4], Accuracy::F32);
+    layer.parameters_mut()[0].1.clone_from(&common::pattern(vec![4], 7.0));
+    common::check_gradients(&mut layer, &input);
+}
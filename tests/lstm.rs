@@ -0,0 +1,131 @@
+mod common;
+
+use littleflow::layer::lstm::{LSTM, LstmState};
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+fn initial_state() -> LstmState<f32> {
+    LstmState {
+        hidden: common::pattern(vec![2, 3], 4.0),
+        cell: common::pattern(vec![2, 3], 9.0),
+    }
+}
+
+#[test]
+fn output_shapes_and_forget_bias() {
+    let input = common::pattern(vec![2, 5, 4], 0.0);
+    let layer = LSTM::<f32>::new(4, 3, Accuracy::F32);
+    assert_eq!(&layer.get_bias().get_data()[3..6], &[1.0, 1.0, 1.0]);
+    assert_eq!(&layer.get_bias().get_data()[6..], &[0.0; 6]);
+
+    let last = layer.forward(&input, None).unwrap();
+    assert_eq!(last.get_shape(), &vec![2, 3]);
+
+    let layer = layer.with_return_sequences(true);
+    let all = layer.forward(&input, None).unwrap();
+    assert_eq!(all.get_shape(), &vec![2, 5, 3]);
+    // The last step of the sequence is the last hidden state
+    assert_eq!(&all.get_data()[12..15], &last.get_data()[..3]);
+
+    // The final state comes next to the output, not inside it
+    let mut layer = layer.with_return_state(true);
+    let (output, state) = layer.forward_with_state(&input, None).unwrap();
+    assert_eq!(output.get_shape(), &vec![2, 5, 3]);
+    assert_eq!(state.hidden.get_data(), last.get_data());
+    assert_eq!(state.cell.get_shape(), &vec![2, 3]);
+
+    assert!(layer.get_final_state().is_none());
+    layer.forward_train(&input, None).unwrap();
+    assert_eq!(layer.get_final_state().unwrap().cell.get_data(), state.cell.get_data());
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let input = common::pattern(vec![2, 4, 3], 0.0);
+
+    let mut layer = LSTM::<f32>::new(3, 3, Accuracy::F32);
+    common::check_gradients(&mut layer, &input);
+
+    let mut layer = LSTM::<f32>::new(3, 3, Accuracy::F32)
+        .with_return_sequences(true)
+        .with_return_state(true);
+    layer.set_initial_state(Some(initial_state()));
+    common::check_gradients(&mut layer, &input);
+}
+
+#[test]
+fn initial_state_is_used_and_differentiated() {
+    let input = common::pattern(vec![2, 3, 2], 1.0);
+    let mut layer = LSTM::<f32>::new(2, 3, Accuracy::F32);
+    let from_zero = layer.forward(&input, None).unwrap();
+
+    layer.set_initial_state(Some(initial_state()));
+    let from_state = layer.forward(&input, None).unwrap();
+    assert_ne!(from_zero.get_data(), from_state.get_data());
+
+    let weights = common::pattern(vec![2, 3], 0.3);
+    layer.backward(&input, &weights);
+    let analytic = layer.get_initial_state_grad().unwrap().cell.get_data()[4];
+
+    let eps = 1e-2;
+    let mut objective = |delta: f32| {
+        let mut state = initial_state();
+        let mut cell = state.cell.get_data().clone();
+        cell[4] += delta;
+        state.cell = Tensor::new(Accuracy::F32, cell, vec![2, 3]);
+        layer.set_initial_state(Some(state));
+        let out = layer.forward(&input, None).unwrap();
+        out.get_data().iter().zip(weights.get_data()).map(|(a, b)| a * b).sum::<f32>()
+    };
+    let numeric = (objective(eps) - objective(-eps)) / (2.0 * eps);
+    assert!((analytic - numeric).abs() < 1e-2, "{} vs {}", analytic, numeric);
+}
+
+#[test]
+fn final_state_gradient_flows_into_the_input() {
+    let input = common::pattern(vec![2, 3, 2], 2.0);
+    let weights = initial_state();
+    let zeros = Tensor::zeros(Accuracy::F32, vec![2, 3]);
+    let mut layer = LSTM::<f32>::new(2, 3, Accuracy::F32).with_return_state(true);
+
+    // Loss = sum(final cell * weights.cell), so the output itself gets no gradient
+    layer.set_final_state_grad(Some(LstmState {
+        hidden: zeros.clone(),
+        cell: weights.cell.clone(),
+    }));
+    layer.forward_train(&input, None).unwrap();
+    let grad = layer.backward(&input, &zeros);
+
+    let eps = 1e-2;
+    let objective = |delta: f32| {
+        let mut data = input.get_data().clone();
+        data[5] += delta;
+        let input = Tensor::new(Accuracy::F32, data, vec![2, 3, 2]);
+        let (_, state) = layer.forward_with_state(&input, None).unwrap();
+        state.cell.get_data().iter().zip(weights.cell.get_data()).map(|(a, b)| a * b).sum::<f32>()
+    };
+    let numeric = (objective(eps) - objective(-eps)) / (2.0 * eps);
+    assert!((grad.get_data()[5] - numeric).abs() < 1e-2, "{} vs {}", grad.get_data()[5], numeric);
+}
+
+#[test]
+fn state_is_reachable_inside_sequential() {
+    let input = common::pattern(vec![2, 3, 2], 1.0);
+    let mut model = Sequential::<f32>::new();
+    model.add(LSTM::<f32>::new(2, 3, Accuracy::F32).with_return_state(true));
+    model.add(DenseLayer::new(3, 1, Accuracy::F32));
+
+    let from_zero = model.forward(&input, &[None, None]);
+    model.layer_mut::<LSTM<f32>>(0).unwrap().set_initial_state(Some(initial_state()));
+    let from_state = model.forward(&input, &[None, None]);
+    assert_ne!(from_zero.get_data(), from_state.get_data());
+
+    model.forward_train(&input, &[None, None]);
+    let lstm = model.layer::<LSTM<f32>>(0).unwrap();
+    assert_eq!(lstm.get_final_state().unwrap().hidden.get_shape(), &vec![2, 3]);
+    assert!(model.layer::<DenseLayer<f32>>(0).is_none());
+    assert!(model.layer::<LSTM<f32>>(2).is_none());
+}
@@ -0,0 +1,53 @@
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+/// Parameterless layer relying on the default parameter API
+struct Negate;
+
+impl TrainableLayer<f32> for Negate {
+    fn forward(&self, input: &Tensor<f32>, _activation: Option<fn(f32) -> f32>) -> Result<Tensor<f32>, String> {
+        Ok(input.scale(-1.0))
+    }
+
+    fn backward(&mut self, _input: &Tensor<f32>, grad_output: &Tensor<f32>) -> Tensor<f32> {
+        grad_output.scale(-1.0)
+    }
+}
+
+#[test]
+fn sequential_exposes_named_parameters_and_gradients() {
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(3, 2, Accuracy::F32));
+    model.add(Negate);
+    model.add(DenseLayer::new(2, 1, Accuracy::F32));
+
+    let names: Vec<String> = model.parameters().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["0.weights", "0.bias", "2.weights", "2.bias"]);
+    assert_eq!(model.num_parameters(), 3 * 2 + 2 + 2 + 1);
+
+    let grad_names: Vec<String> = model.gradients().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(grad_names, names);
+
+    let summary = model.summary();
+    assert!(summary.contains("1: Negate (0 params)"));
+    assert!(summary.contains("0: DenseLayer (8 params)"));
+    assert!(summary.contains("Total params: 11"));
+}
+
+#[test]
+fn dense_backward_accumulates_until_zero_grad() {
+    let mut layer = DenseLayer::<f32>::new(2, 1, Accuracy::F32);
+    let input = Tensor::new(Accuracy::F32, vec![1.0, 2.0], vec![1, 2]);
+    let grad_output = Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]);
+
+    TrainableLayer::backward(&mut layer, &input, &grad_output);
+    TrainableLayer::backward(&mut layer, &input, &grad_output);
+    assert_eq!(layer.gradients()[0].1.get_data(), &vec![2.0, 4.0]);
+    assert_eq!(layer.gradients()[1].1.get_data(), &vec![2.0]);
+
+    layer.zero_grad();
+    assert!(layer.gradients().iter().all(|(_, grad)| grad.get_data().iter().all(|&g| g == 0.0)));
+}
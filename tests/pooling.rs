@@ -0,0 +1,106 @@
+mod common;
+
+use littleflow::layer::pooling::{AvgPool1D, AvgPool2D, GlobalAvgPool, GlobalMaxPool, MaxPool1D, MaxPool2D};
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+fn grid() -> Tensor<f32> {
+    let data = vec![
+        1.0, 5.0, 2.0, 0.0, //
+        3.0, 4.0, 8.0, 6.0, //
+        7.0, 0.0, 1.0, 2.0, //
+        9.0, 1.0, 3.0, 4.0,
+    ];
+    Tensor::new(Accuracy::F32, data, vec![1, 1, 4, 4])
+}
+
+#[test]
+fn max_pool_routes_gradient_to_argmax() {
+    let input = grid();
+    let mut pool = MaxPool2D::new((2, 2));
+
+    let output = pool.forward_train(&input, None).unwrap();
+    assert_eq!(output.get_shape(), &vec![1, 1, 2, 2]);
+    assert_eq!(output.get_data(), &vec![5.0, 8.0, 9.0, 4.0]);
+
+    let grad_output = Tensor::new(Accuracy::F32, vec![1.0, 2.0, 3.0, 4.0], vec![1, 1, 2, 2]);
+    let grad = pool.backward(&input, &grad_output);
+    let expected = vec![
+        0.0, 1.0, 0.0, 0.0, //
+        0.0, 0.0, 2.0, 0.0, //
+        0.0, 0.0, 0.0, 0.0, //
+        3.0, 0.0, 0.0, 4.0,
+    ];
+    assert_eq!(grad.get_data(), &expected);
+
+    // Overlapping windows accumulate into the shared maximum
+    let mut overlapping = MaxPool2D::new((2, 2)).with_stride((1, 1));
+    let output = overlapping.forward_train(&input, None).unwrap();
+    assert_eq!(output.get_shape(), &vec![1, 1, 3, 3]);
+    let grad = overlapping.backward(&input, &Tensor::new(Accuracy::F32, vec![1.0; 9], vec![1, 1, 3, 3]));
+    assert_eq!(grad.get_data()[6], 4.0);
+}
+
+#[test]
+fn max_pool_backward_follows_the_input_it_is_given() {
+    let a = grid();
+    let b = Tensor::new(Accuracy::F32, a.get_data().iter().rev().copied().collect(), vec![1, 1, 4, 4]);
+    let grad_output = Tensor::new(Accuracy::F32, vec![1.0; 4], vec![1, 1, 2, 2]);
+
+    let mut pool = MaxPool2D::new((2, 2));
+    pool.forward_train(&a, None).unwrap();
+    let expected = pool.backward(&a, &grad_output);
+    pool.forward_train(&a, None).unwrap();
+    pool.forward_train(&b, None).unwrap();
+    assert_eq!(pool.backward(&a, &grad_output).get_data(), expected.get_data());
+    assert_eq!(pool.backward(&a, &grad_output).get_data(), expected.get_data());
+}
+
+#[test]
+fn avg_pool_values_and_gradients() {
+    let input = grid();
+    let pool = AvgPool2D::new((2, 2));
+    let output = pool.forward(&input, None).unwrap();
+    assert_eq!(output.get_data(), &vec![3.25, 4.0, 4.25, 2.5]);
+
+    common::check_gradients(&mut AvgPool2D::new((2, 3)).with_stride((1, 2)), &common::pattern(vec![2, 2, 4, 5], 0.0));
+    common::check_gradients(&mut AvgPool1D::new(3).with_stride(2), &common::pattern(vec![2, 3, 7], 0.5));
+}
+
+#[test]
+fn one_dimensional_pools() {
+    let input = Tensor::new(Accuracy::F32, vec![1.0, 3.0, 2.0, 5.0, 4.0, 0.0], vec![1, 1, 6]);
+
+    let mut max = MaxPool1D::new(2);
+    let output = max.forward_train(&input, None).unwrap();
+    assert_eq!(output.get_shape(), &vec![1, 1, 3]);
+    assert_eq!(output.get_data(), &vec![3.0, 5.0, 4.0]);
+    let grad = max.backward(&input, &Tensor::new(Accuracy::F32, vec![1.0, 2.0, 3.0], vec![1, 1, 3]));
+    assert_eq!(grad.get_data(), &vec![0.0, 1.0, 0.0, 2.0, 3.0, 0.0]);
+
+    let avg = AvgPool1D::new(3).with_stride(3);
+    assert_eq!(avg.forward(&input, None).unwrap().get_data(), &vec![2.0, 3.0]);
+}
+
+#[test]
+fn global_pools_reduce_spatial_dims() {
+    let input = grid().reshape(vec![1, 2, 2, 4]);
+
+    let mut avg = GlobalAvgPool;
+    let output = avg.forward(&input, None).unwrap();
+    assert_eq!(output.get_shape(), &vec![1, 2]);
+    assert_eq!(output.get_data(), &vec![3.625, 3.375]);
+    let grad = avg.backward(&input, &Tensor::new(Accuracy::F32, vec![8.0, 16.0], vec![1, 2]));
+    assert_eq!(grad.get_data(), &[vec![1.0; 8], vec![2.0; 8]].concat());
+
+    let mut max = GlobalMaxPool;
+    let output = max.forward_train(&input, None).unwrap();
+    assert_eq!(output.get_data(), &vec![8.0, 9.0]);
+    let grad = max.backward(&input, &Tensor::new(Accuracy::F32, vec![1.0, 1.0], vec![1, 2]));
+    assert_eq!(grad.get_data().iter().sum::<f32>(), 2.0);
+    assert_eq!(grad.get_data()[6], 1.0);
+    assert_eq!(grad.get_data()[12], 1.0);
+
+    assert!(TrainableLayer::<f32>::parameters(&max).is_empty());
+}
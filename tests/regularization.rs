@@ -0,0 +1,51 @@
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::regularizer::{Constraint, Regularizer};
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn regularizer_penalty_and_gradient() {
+    let w = Tensor::new(Accuracy::F32, vec![1.0, -2.0, 0.0], vec![3]);
+
+    assert_eq!(Regularizer::L1(0.5).penalty(&w), 1.5);
+    assert_eq!(Regularizer::L2(0.5).penalty(&w), 2.5);
+    assert_eq!(Regularizer::ElasticNet { l1: 0.5, l2: 0.5 }.penalty(&w), 4.0);
+
+    assert_eq!(Regularizer::L1(0.5).gradient(&w).get_data(), &vec![0.5, -0.5, 0.0]);
+    assert_eq!(Regularizer::L2(0.5).gradient(&w).get_data(), &vec![1.0, -2.0, 0.0]);
+}
+
+#[test]
+fn constraints_project_parameters() {
+    let w: Tensor<f32> = Tensor::new(Accuracy::F32, vec![3.0, 0.1, 4.0, -0.2], vec![2, 2]);
+
+    let clipped = Constraint::MaxNorm(1.0).apply(&w);
+    assert!((clipped.get_data()[0] - 0.6).abs() < 1e-6);
+    assert!((clipped.get_data()[2] - 0.8).abs() < 1e-6);
+    // second column already has norm < 1
+    assert_eq!(clipped.get_data()[1], 0.1);
+
+    assert_eq!(Constraint::NonNeg.apply(&w).get_data(), &vec![3.0, 0.1, 4.0, 0.0]);
+}
+
+#[test]
+fn dense_layer_adds_penalty_and_applies_constraint() {
+    let mut layer = DenseLayer::<f32>::new(2, 2, Accuracy::F32)
+        .with_kernel_regularizer(Regularizer::L2(0.1))
+        .with_kernel_constraint(Constraint::NonNeg);
+    layer.set_weights(&Tensor::new(Accuracy::F32, vec![1.0, -1.0, 2.0, 0.5], vec![2, 2]));
+
+    assert!((layer.regularization_loss() - 0.625).abs() < 1e-6);
+
+    // With a zero output gradient only the penalty gradient remains: 2 * 0.1 * w
+    let input = Tensor::new(Accuracy::F32, vec![1.0, 1.0], vec![1, 2]);
+    let zero = Tensor::zeros(Accuracy::F32, vec![1, 2]);
+    TrainableLayer::backward(&mut layer, &input, &zero);
+    let grad_w = layer.gradients()[0].1.clone();
+    assert!((grad_w.get_data()[1] + 0.2).abs() < 1e-6);
+
+    layer.set_weights(&layer.get_weights().sub(&grad_w));
+    layer.apply_constraints();
+    assert!(layer.get_weights().get_data().iter().all(|&x| x >= 0.0));
+}
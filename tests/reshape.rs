@@ -0,0 +1,44 @@
+use littleflow::layer::conv::{Conv2D, Conv2DConfig};
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::pooling::MaxPool2D;
+use littleflow::layer::reshape::{Flatten, Reshape};
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::model::sequential::Sequential;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn flatten_and_reshape_round_trip() {
+    let input = Tensor::new(Accuracy::F32, (0..24).map(|x| x as f32).collect(), vec![2, 3, 2, 2]);
+
+    let mut flatten = Flatten;
+    let flat = flatten.forward(&input, None).unwrap();
+    assert_eq!(flat.get_shape(), &vec![2, 12]);
+    assert_eq!(flat.get_data(), input.get_data());
+    assert_eq!(flatten.backward(&input, &flat).get_shape(), input.get_shape());
+
+    let mut reshape = Reshape::new(vec![4, 3]);
+    let out = reshape.forward(&input, None).unwrap();
+    assert_eq!(out.get_shape(), &vec![2, 4, 3]);
+    assert_eq!(reshape.backward(&input, &out).get_shape(), input.get_shape());
+
+    assert!(Reshape::new(vec![5]).forward(&input, None).is_err());
+}
+
+#[test]
+fn conv_stack_feeds_a_dense_head() {
+    let mut model: Sequential<f32> = Sequential::new();
+    model.add(Conv2D::new(1, 2, Conv2DConfig::new((3, 3)), Accuracy::F32));
+    model.add(MaxPool2D::new((2, 2)));
+    model.add(Flatten);
+    model.add(DenseLayer::new(8, 1, Accuracy::F32));
+    let activations = [None, None, None, None];
+
+    let input = Tensor::new(Accuracy::F32, (0..36).map(|x| (x as f32 * 0.3).sin()).collect(), vec![1, 1, 6, 6]);
+    let cache = model.forward_train(&input, &activations);
+    assert_eq!(cache.output().get_shape(), &vec![1, 1]);
+
+    let grad = model.backward(&cache, &Tensor::new(Accuracy::F32, vec![1.0], vec![1, 1]));
+    assert_eq!(grad.get_shape(), input.get_shape());
+    assert!(model.gradients().iter().any(|(_, g)| g.get_data().iter().any(|&x| x != 0.0)));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use littleflow::layer::initializer::Initializer;
+use littleflow::layer::rnn::{RNN, RecurrentActivation};
+use littleflow::layer::trainable::TrainableLayer;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn linear_rnn_accumulates_the_sequence() {
+    // h_t = x_t + 0.5 * h_{t-1}
+    let layer = RNN::<f32>::with_initializers(
+        1,
+        1,
+        Accuracy::F32,
+        &Initializer::Constant(1.0),
+        &Initializer::Constant(0.5),
+        &Initializer::Zeros,
+    )
+    .with_activation(RecurrentActivation::Linear);
+    let input = Tensor::new(Accuracy::F32, vec![1.0, 2.0, 4.0], vec![1, 3, 1]);
+
+    let last = layer.forward(&input, None).unwrap();
+    assert_eq!(last.get_shape(), &vec![1, 1]);
+    assert_eq!(last.get_data(), &vec![5.25]);
+
+    let all = layer.with_return_sequences(true).forward(&input, None).unwrap();
+    assert_eq!(all.get_shape(), &vec![1, 3, 1]);
+    assert_eq!(all.get_data(), &vec![1.0, 2.5, 5.25]);
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let input = common::pattern(vec![2, 4, 3], 0.0);
+    for (activation, return_sequences) in [
+        (RecurrentActivation::Tanh, false),
+        (RecurrentActivation::Sigmoid, true),
+    ] {
+        let mut layer = RNN::<f32>::new(3, 2, Accuracy::F32)
+            .with_activation(activation)
+            .with_return_sequences(return_sequences);
+        layer.parameters_mut()[2].1.clone_from(&common::pattern(vec![2], 3.0));
+        common::check_gradients(&mut layer, &input);
+    }
+}
+
+#[test]
+fn truncated_bptt_stops_gradient_at_chunk_boundaries() {
+    let input = common::pattern(vec![1, 4, 2], 0.0);
+    let mut layer = RNN::<f32>::new(2, 3, Accuracy::F32).with_truncated_bptt(2);
+
+    let out = layer.forward(&input, None).unwrap();
+    let grad = layer.backward(&input, &Tensor::new(Accuracy::F32, vec![1.0; 3], out.get_shape().clone()));
+    let data = grad.get_data();
+    assert!(data[..4].iter().all(|&g| g == 0.0));
+    assert!(data[4..].iter().any(|&g| g != 0.0));
+}
@@ -0,0 +1,47 @@
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::trainable::TrainableModel;
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::model::sequential::Sequential;
+use littleflow::rng;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+fn run(seed: u64) -> (Vec<f32>, Vec<f32>) {
+    rng::set_seed(seed);
+
+    let inputs: Vec<Tensor<f32>> = (0..8)
+        .map(|i| Tensor::new(Accuracy::F32, vec![i as f32 / 8.0, 1.0 - i as f32 / 8.0], vec![1, 2]))
+        .collect();
+    let targets: Vec<Tensor<f32>> = (0..8)
+        .map(|i| Tensor::new(Accuracy::F32, vec![(i % 2) as f32], vec![1, 1]))
+        .collect();
+
+    let mut model = Sequential::<f32>::new();
+    model.add(DenseLayer::new(2, 4, Accuracy::F32));
+    model.add(DenseLayer::new(4, 1, Accuracy::F32));
+    model.set_shuffle(true);
+
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 5, 0.1, &[None, None]);
+    let params = model
+        .parameters()
+        .into_iter()
+        .flat_map(|(_, p)| p.get_data().clone())
+        .collect();
+    (history.loss().to_vec(), params)
+}
+
+#[test]
+fn same_seed_reproduces_training() {
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7).1, run(8).1);
+    assert_eq!(rng::seed(), Some(8));
+}
+
+#[test]
+fn explicit_rng_initialisation() {
+    let a = DenseLayer::<f32>::new_with_rng(3, 2, Accuracy::F32, &mut StdRng::seed_from_u64(1));
+    let b = DenseLayer::<f32>::new_with_rng(3, 2, Accuracy::F32, &mut StdRng::seed_from_u64(1));
+    assert_eq!(a.get_weights().get_data(), b.get_weights().get_data());
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use littleflow::layer::dense::DenseLayer;
+use littleflow::layer::embedding::Embedding;
+use littleflow::layer::positional::{LearnedPositionalEncoding, SinusoidalPositionalEncoding};
+use littleflow::layer::reshape::Flatten;
+use littleflow::layer::trainable::{TrainableLayer, TrainableModel};
+use littleflow::layer::transformer::TransformerEncoderLayer;
+use littleflow::loss::mse::MeanSquaredError;
+use littleflow::model::sequential::Sequential;
+use littleflow::optim::clip::GradientClipping;
+use littleflow::rng;
+use littleflow::tensor::Tensor;
+use littleflow::types::Accuracy;
+
+#[test]
+fn positional_encodings() {
+    let zeros = Tensor::new(Accuracy::F32, vec![0.0; 8], vec![1, 2, 4]);
+    let sinusoidal = SinusoidalPositionalEncoding::<f32>::new(10, 4);
+    let out = sinusoidal.forward(&zeros, None).unwrap();
+    let expected = [0.0, 1.0, 0.0, 1.0, 1f32.sin(), 1f32.cos(), 0.01f32.sin(), 0.01f32.cos()];
+    for (got, want) in out.get_data().iter().zip(expected) {
+        assert!((got - want).abs() < 1e-6);
+    }
+    assert!(sinusoidal.forward(&Tensor::new(Accuracy::F32, vec![0.0; 44], vec![1, 11, 4]), None).is_err());
+
+    let mut learned = LearnedPositionalEncoding::<f32>::new(5, 4, Accuracy::F32);
+    common::check_gradients(&mut learned, &common::pattern(vec![2, 3, 4], 0.0));
+    // Unused positions get no gradient
+    assert_eq!(&learned.gradients()[0].1.get_data()[12..], &[0.0; 8]);
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let input = common::pattern(vec![2, 3, 4], 0.0);
+    for norm_first in [false, true] {
+        rng::set_seed(1);
+        let mut layer = TransformerEncoderLayer::<f32>::new(4, 2, 6, Accuracy::F32).with_norm_first(norm_first);
+        // A small step keeps the perturbations from crossing the ReLU kinks of the feed-forward block
+        common::check_gradients_with_eps(&mut layer, &input, 1e-3);
+    }
+}
+
+#[test]
+fn backward_reuses_the_training_pass() {
+    rng::set_seed(5);
+    let input = common::pattern(vec![2, 3, 4], 0.0);
+    let other = common::pattern(vec![2, 3, 4], 2.0);
+    let grad_output = common::pattern(vec![2, 3, 4], 1.0);
+    let mut layer = TransformerEncoderLayer::<f32>::new(4, 2, 6, Accuracy::F32).with_dropout(0.3);
+    layer.set_training(true);
+
+    // The dropout masks belong to the pass, so it can be backpropagated again later
+    layer.forward_train(&input, None).unwrap();
+    let state = layer.pass_state();
+    let expected = layer.backward(&input, &grad_output);
+    assert_eq!(layer.backward(&input, &grad_output).get_data(), expected.get_data());
+
+    layer.forward_train(&other, None).unwrap();
+    assert_ne!(layer.backward(&input, &grad_output).get_data(), expected.get_data());
+    layer.set_pass_state(state);
+    assert_eq!(layer.backward(&input, &grad_output).get_data(), expected.get_data());
+}
+
+#[test]
+#[should_panic(expected = "forward_train")]
+fn backward_without_training_pass_panics() {
+    let input = common::pattern(vec![2, 3, 4], 0.0);
+    let mut layer = TransformerEncoderLayer::<f32>::new(4, 2, 6, Accuracy::F32);
+    layer.backward(&input, &input);
+}
+
+#[test]
+fn encoders_stack_inside_sequential() {
+    let inputs: Vec<Tensor<f32>> = (0..4)
+        .map(|i| Tensor::new(Accuracy::F32, vec![i as f32, ((i + 1) % 4) as f32, 2.0], vec![1, 3]))
+        .collect();
+    let targets: Vec<Tensor<f32>> = (0..4)
+        .map(|i| Tensor::new(Accuracy::F32, vec![i as f32 / 4.0], vec![1, 1]))
+        .collect();
+
+    rng::set_seed(0);
+    let mut model = Sequential::<f32>::new();
+    model.add(Embedding::new(4, 8, Accuracy::F32));
+    model.add(SinusoidalPositionalEncoding::new(16, 8));
+    model.add(TransformerEncoderLayer::new(8, 2, 16, Accuracy::F32).with_dropout(0.1));
+    model.add(TransformerEncoderLayer::new(8, 2, 16, Accuracy::F32).with_norm_first(true));
+    model.add(Flatten);
+    model.add(DenseLayer::new(24, 1, Accuracy::F32));
+    model.set_gradient_clipping(Some(GradientClipping::GlobalNorm(1.0)));
+    assert!(model.parameters().iter().any(|(name, _)| name == "2.attention.query_weights"));
+
+    let activations = [None; 6];
+    let before: Vec<f32> = model.parameters()[4].1.get_data().clone();
+    let history = model.train(&inputs, &targets, &MeanSquaredError, 40, 0.02, &activations);
+    assert!(history.loss().iter().all(|loss| loss.is_finite()));
+    assert!(history.loss().last().unwrap() < &history.loss()[0]);
+    assert_ne!(model.parameters()[4].1.get_data(), &before);
+
+    // Inference is deterministic once training mode is off
+    let a = model.forward(&inputs[0], &activations);
+    let b = model.forward(&inputs[0], &activations);
+    assert_eq!(a.get_data(), b.get_data());
+}